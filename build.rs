@@ -0,0 +1,19 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "cbindgen")]
+    {
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+
+        // Only the FFI module is parsed, the rest of the crate is not exported
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_root_or_default(&crate_dir);
+        cbindgen::Builder::new()
+            .with_src(format!("{crate_dir}/src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("generating the C header failed")
+            .write_to_file("include/zap_me.h");
+    }
+}
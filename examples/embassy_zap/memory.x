@@ -0,0 +1,6 @@
+/* The boot loader in BOOT2 is placed by link-rp.x of embassy-rp */
+MEMORY {
+    BOOT2 : ORIGIN = 0x10000000, LENGTH = 0x100
+    FLASH : ORIGIN = 0x10000100, LENGTH = 2048K - 0x100
+    RAM   : ORIGIN = 0x20000000, LENGTH = 256K
+}
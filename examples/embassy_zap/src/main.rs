@@ -0,0 +1,44 @@
+//! Runs the transmitter on an RP2040 with embassy instead of rp2040-hal.
+//!
+//! The embassy time driver provides both the delay and the clock, so the
+//! `now_fn` converts `embassy_time::Instant` to the microsecond
+//! `fugit::Instant` of zap-me. This requires the default tick rate of
+//! embassy-rp of 1 MHz. Commands are sent with blocking delays, which stall
+//! the executor, so other tasks only run while waiting between commands.
+
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+use embassy_executor::Spawner;
+use embassy_rp::gpio::{Level, Output};
+use embassy_time::{Delay, Timer};
+use {defmt_rtt as _, panic_probe as _};
+
+use zap_me::ch8803::{Instant, Transmitter as ZapMe};
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let p = embassy_rp::init(Default::default());
+
+    let zap = Output::new(p.PIN_16, Level::Low);
+    let delay = RefCell::new(Delay);
+    let now_fn =
+        || Instant::from_ticks(embassy_time::Instant::now().as_micros());
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&delay)
+        .now_fn(now_fn)
+        .id(0x0D25)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+
+    loop {
+        defmt::info!("vibrating");
+        collar.vibrate_ms(1, 2000);
+        collar.vibrate_ms(99, 2000);
+        collar.beep_ms(250);
+        Timer::after_secs(5).await;
+    }
+}
@@ -0,0 +1,142 @@
+//! Runs the transmitter on an ESP32 with ESP-IDF.
+//!
+//! ESP-IDF provides `std`, but zap-me itself stays `no_std` and only uses the
+//! `embedded-hal` traits implemented by `esp-idf-hal`, so the same code also
+//! works with the bare-metal `esp-hal`.
+
+use core::cell::RefCell;
+use esp_idf_svc::eventloop::EspSystemEventLoop;
+use esp_idf_svc::hal::delay::{Ets, FreeRtos};
+use esp_idf_svc::hal::gpio::PinDriver;
+use esp_idf_svc::hal::modem::Modem;
+use esp_idf_svc::hal::peripherals::Peripherals;
+use esp_idf_svc::hal::timer::{TimerConfig, TimerDriver};
+use esp_idf_svc::mqtt::client::{
+    EspMqttClient, EventPayload, MqttClientConfiguration, QoS,
+};
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::wifi::{
+    BlockingWifi, ClientConfiguration, Configuration, EspWifi,
+};
+
+use zap_me::ch8803::{Instant, Transmitter as ZapMe};
+use zap_me::mqtt_bridge::{MqttBridge, TOPIC_FILTER};
+
+// Leave empty to run the demo pattern without Wi-Fi
+const SSID: &str = "";
+const PASSWORD: &str = "";
+// The broker to receive commands from, e.g. the one in mosquitto/
+const MQTT_URL: &str = "mqtt://192.168.1.2:1883";
+
+fn main() -> anyhow::Result<()> {
+    esp_idf_svc::sys::link_patches();
+    esp_idf_svc::log::EspLogger::initialize_default();
+
+    let peripherals = Peripherals::take()?;
+
+    let wifi = if SSID.is_empty() {
+        None
+    } else {
+        Some(connect_wifi(
+            peripherals.modem,
+            EspSystemEventLoop::take()?,
+            EspDefaultNvsPartition::take()?,
+        )?)
+    };
+
+    // The default divider of 80 makes the timer count in microseconds
+    let mut timer = TimerDriver::new(peripherals.timer00, &TimerConfig::new())?;
+    timer.enable(true)?;
+
+    let delay = RefCell::new(Ets);
+    let zap = PinDriver::output(peripherals.pins.gpio4)?;
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&delay)
+        .now_fn(|| Instant::from_ticks(timer.counter().unwrap()))
+        .id(0x0D25)
+        .build();
+
+    if wifi.is_some() {
+        let (mut client, mut connection) = EspMqttClient::new(
+            MQTT_URL,
+            &MqttClientConfiguration {
+                client_id: Some("zap-me"),
+                ..Default::default()
+            },
+        )?;
+
+        // The connection has to be polled while a command is being sent
+        let (sender, messages) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .stack_size(6000)
+            .spawn(move || {
+                while let Ok(event) = connection.next() {
+                    if let EventPayload::Received {
+                        topic: Some(topic),
+                        data,
+                        ..
+                    } = event.payload()
+                    {
+                        let _ = sender.send((topic.to_owned(), data.to_vec()));
+                    }
+                }
+            })?;
+
+        while let Err(err) = client.subscribe(TOPIC_FILTER, QoS::AtMostOnce) {
+            log::warn!("Subscribing failed: {err}, retrying");
+            FreeRtos::delay_ms(500);
+        }
+
+        // Stay below the task watchdog timeout of sdkconfig.defaults
+        let bridge = MqttBridge::with_max_duration(5000);
+        for (topic, payload) in messages {
+            match bridge.handle_message(&topic, &payload, &mut transmitter) {
+                Ok(packet) => log::info!("Sent {packet:?}"),
+                Err(err) => log::warn!("Ignored message on {topic}: {err:?}"),
+            }
+        }
+        return Ok(());
+    }
+
+    let mut collar = transmitter.channel(0).unwrap();
+
+    loop {
+        // Send weakest and strongest vibration pulse for 2 seconds each
+        collar.vibrate_ms(1, 2000);
+        collar.vibrate_ms(99, 2000);
+
+        // Send one audio warning (250ms is enough for one beep iteration)
+        collar.beep_ms(250);
+        FreeRtos::delay_ms(2000);
+
+        // Send shock on level 1
+        //collar.shock_ms(1, 500);
+        FreeRtos::delay_ms(3000);
+    }
+}
+
+/// Connects to the configured access point.
+fn connect_wifi(
+    modem: Modem,
+    sysloop: EspSystemEventLoop,
+    nvs: EspDefaultNvsPartition,
+) -> anyhow::Result<BlockingWifi<EspWifi<'static>>> {
+    let mut wifi = BlockingWifi::wrap(
+        EspWifi::new(modem, sysloop.clone(), Some(nvs))?,
+        sysloop,
+    )?;
+
+    wifi.set_configuration(&Configuration::Client(ClientConfiguration {
+        ssid: SSID.try_into().unwrap(),
+        password: PASSWORD.try_into().unwrap(),
+        ..Default::default()
+    }))?;
+    wifi.start()?;
+    wifi.connect()?;
+    wifi.wait_netif_up()?;
+
+    log::info!("Wi-Fi connected");
+    Ok(wifi)
+}
@@ -0,0 +1,7 @@
+/* Layout without a SoftDevice. With the S140 flashed, the application starts
+   at 0x27000 and the RAM origin moves up by the amount the SoftDevice needs,
+   e.g. FLASH : ORIGIN = 0x00027000, LENGTH = 868K */
+MEMORY {
+    FLASH : ORIGIN = 0x00000000, LENGTH = 1024K
+    RAM   : ORIGIN = 0x20000000, LENGTH = 256K
+}
@@ -0,0 +1,69 @@
+//! Runs the transmitter on an nRF52840.
+//!
+//! The pulse timing is generated by busy-waiting, so anything preempting the
+//! CPU stretches the pulses. When the SoftDevice is enabled, its radio events
+//! run at the highest interrupt priority and will corrupt packets. Either
+//! disable the SoftDevice (`sd_softdevice_disable`) while sending commands, or
+//! request a radio timeslot (`sd_radio_session_open` and `sd_radio_request`)
+//! that covers the command duration, during which the SoftDevice stays idle.
+
+#![no_std]
+#![no_main]
+
+use core::cell::{Cell, RefCell};
+use cortex_m_rt::entry;
+use nrf52840_hal::{Timer, gpio::Level, gpio::p0, pac};
+use panic_halt as _;
+
+use zap_me::ch8803::{Duration, Instant, Transmitter as ZapMe};
+
+#[entry]
+fn main() -> ! {
+    let p = pac::Peripherals::take().unwrap();
+
+    // TIMER0 provides the delays while TIMER1 free-runs at 1 MHz as clock.
+    // TIMER1 wraps after about 71 minutes, so the wraps are counted to get a
+    // monotonic 64-bit Instant.
+    let delay = RefCell::new(Timer::new(p.TIMER0));
+    let mut clock = Timer::periodic(p.TIMER1);
+    clock.start(u32::MAX);
+    let last = Cell::new(0u32);
+    let wraps = Cell::new(0u64);
+    let now_fn = || {
+        let ticks = clock.read();
+        if ticks < last.get() {
+            wraps.set(wraps.get() + 1);
+        }
+        last.set(ticks);
+        Instant::from_ticks((wraps.get() << 32) | u64::from(ticks))
+    };
+
+    let port0 = p0::Parts::new(p.P0);
+    let zap = port0.p0_13.into_push_pull_output(Level::Low);
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&delay)
+        .now_fn(now_fn)
+        .id(0x0D25)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+
+    loop {
+        // Send weakest and strongest vibration pulse for 2 seconds each
+        collar.vibrate_ms(1, 2000);
+        collar.vibrate_ms(99, 2000);
+
+        // Send one audio warning (250ms is enough for one beep iteration)
+        collar.beep_ms(250);
+        pause(&delay, Duration::millis(2000));
+
+        // Send shock on level 1
+        //collar.shock_ms(1, 500);
+        pause(&delay, Duration::millis(3000));
+    }
+}
+
+fn pause(delay: &RefCell<Timer<pac::TIMER0>>, duration: Duration) {
+    delay.borrow_mut().delay(duration.ticks());
+}
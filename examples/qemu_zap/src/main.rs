@@ -0,0 +1,66 @@
+//! Runs one vibrate and beep cycle under QEMU, so the transmitter can be
+//! exercised in CI without hardware. Run it with `make qemu` from the
+//! repository root, which requires `qemu-system-arm` and the
+//! `thumbv7m-none-eabi` target.
+//!
+//! The emulated LM3S6965 has no accurate timer, so the delays come from a
+//! [MockTimingSource] and the pin only counts edges. The results are printed
+//! through semihosting, and a panic exits QEMU with a failure status.
+
+#![no_std]
+#![no_main]
+
+use core::cell::{Cell, RefCell};
+use core::convert::Infallible;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::{debug, hprintln};
+use embedded_hal::digital::{ErrorType, OutputPin};
+use panic_semihosting as _;
+
+use zap_me::ch8803::Transmitter as ZapMe;
+use zap_me::timing::{self, MockTimingSource, TimingSource};
+
+/// A pin that counts how often it was driven high.
+struct EdgeCounter<'a>(&'a Cell<u32>);
+
+impl ErrorType for EdgeCounter<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for EdgeCounter<'_> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.set(self.0.get() + 1);
+        Ok(())
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    let delay = RefCell::new(MockTimingSource::default());
+    let rising = Cell::new(0);
+
+    let mut transmitter = ZapMe::builder()
+        .pin(EdgeCounter(&rising))
+        .delay(&delay)
+        .now_fn(timing::now_fn(&delay))
+        .id(0x0D25)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+    collar.vibrate_ms(20, 500);
+    collar.beep_ms(250);
+
+    let elapsed = delay.borrow().now().duration_since_epoch();
+    hprintln!(
+        "cycle complete: {} pulses in {} ms",
+        rising.get(),
+        elapsed.to_millis()
+    );
+    debug::exit(debug::EXIT_SUCCESS);
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
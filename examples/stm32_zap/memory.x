@@ -0,0 +1,4 @@
+MEMORY {
+    FLASH : ORIGIN = 0x08000000, LENGTH = 512K
+    RAM   : ORIGIN = 0x20000000, LENGTH = 128K
+}
@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+use core::cell::{Cell, RefCell};
+use cortex_m_rt::entry;
+use panic_halt as _;
+use stm32f4xx_hal::{pac, prelude::*, rcc::Config};
+
+use zap_me::ch8803::{Duration, Instant, Transmitter as ZapMe};
+
+#[entry]
+fn main() -> ! {
+    let dp = pac::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Black Pill board with a 25 MHz crystal
+    let mut rcc = dp.RCC.freeze(Config::hse(25.MHz()).sysclk(100.MHz()));
+
+    let delay = RefCell::new(cp.SYST.delay(&rcc.clocks));
+
+    // TIM2 is a free-running 32-bit microsecond counter. It wraps after about
+    // 71 minutes, so the wraps are counted to get a monotonic 64-bit Instant.
+    let mut counter = dp.TIM2.counter_us(&mut rcc);
+    counter.start(u32::MAX.micros()).unwrap();
+    let last = Cell::new(0u32);
+    let wraps = Cell::new(0u64);
+    let now_fn = || {
+        let ticks = counter.now().ticks();
+        if ticks < last.get() {
+            wraps.set(wraps.get() + 1);
+        }
+        last.set(ticks);
+        Instant::from_ticks((wraps.get() << 32) | u64::from(ticks))
+    };
+
+    let gpioa = dp.GPIOA.split(&mut rcc);
+    let zap = gpioa.pa0.into_push_pull_output();
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&delay)
+        .now_fn(now_fn)
+        .id(0x0D25)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+
+    loop {
+        // Send weakest and strongest vibration pulse for 2 seconds each
+        collar.vibrate_ms(1, 2000);
+        collar.vibrate_ms(99, 2000);
+
+        // Send one audio warning (250ms is enough for one beep iteration)
+        collar.beep_ms(250);
+        delay.borrow_mut().delay(Duration::millis(2000));
+
+        // Send shock on level 1
+        //collar.shock_ms(1, 500);
+        delay.borrow_mut().delay(Duration::millis(3000));
+    }
+}
@@ -0,0 +1,9 @@
+//! Browser demo of the `wasm` feature. Build with
+//!
+//! ```sh
+//! wasm-pack build --target web
+//! ```
+//!
+//! and serve this directory, then open `index.html`.
+
+pub use zap_me::wasm::JsTransmitter;
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+use cortex_m_rt::entry;
+use panic_halt as _;
+use rp2040_hal::{
+    adc::{Adc, AdcPin},
+    gpio::Pins,
+    pac,
+    sio::Sio,
+    timer::Timer,
+    watchdog::Watchdog,
+};
+
+// SAFETY: the boot loader has to be in the .boot2 section, which memory.x
+// places at the start of flash where the RP2040 boot ROM expects it
+#[unsafe(link_section = ".boot2")]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+use zap_me::ch8803::{Command, Transmitter as ZapMe};
+use zap_me::timing::{self, FugitTimer};
+
+#[entry]
+fn main() -> ! {
+    let mut pac = pac::Peripherals::take().unwrap();
+
+    let mut watchdog = Watchdog::new(pac.WATCHDOG);
+
+    let clocks = rp2040_hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let timer = RefCell::new(FugitTimer(Timer::new(
+        pac.TIMER,
+        &mut pac.RESETS,
+        &clocks,
+    )));
+
+    let sio = Sio::new(pac.SIO);
+    let pins = Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+
+    let zap = pins.gpio16.into_push_pull_output();
+
+    // Potentiometer wiper on GPIO26 (ADC0), outer legs on 3V3 and GND
+    let mut adc = Adc::new(pac.ADC, &mut pac.RESETS);
+    let mut pot = AdcPin::new(pins.gpio26.into_floating_input()).unwrap();
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&timer)
+        .now_fn(timing::now_fn(&timer))
+        .id(0x0D25)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+
+    // Follow the potentiometer position with the vibration strength
+    let _ = collar.follow_analog(&mut adc, &mut pot, Command::Vibrate, 100);
+
+    // Reading the RP2040 ADC can't fail, so this is never reached
+    unreachable!()
+}
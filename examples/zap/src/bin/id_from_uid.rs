@@ -0,0 +1,84 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+use cortex_m_rt::entry;
+use panic_halt as _;
+use rp2040_hal::{
+    Clock, gpio::Pins, pac, sio::Sio, timer::Timer, watchdog::Watchdog,
+};
+
+// SAFETY: the boot loader has to be in the .boot2 section, which memory.x
+// places at the start of flash where the RP2040 boot ROM expects it
+#[unsafe(link_section = ".boot2")]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+use zap_me::ch8803::{Transmitter as ZapMe, id_from_seed};
+use zap_me::timing::{self, FugitTimer};
+
+#[entry]
+fn main() -> ! {
+    let mut pac = pac::Peripherals::take().unwrap();
+    let core = pac::CorePeripherals::take().unwrap();
+
+    // Read the unique ID of the flash chip before anything else runs, so
+    // every board pairs with its own ID without hardcoding one
+    let mut uid = [0; 8];
+    // SAFETY: interrupts are disabled, the second core is not started and
+    // no DMA is set up, so nothing accesses the flash in the meantime
+    cortex_m::interrupt::free(|_| unsafe {
+        rp2040_flash::flash::flash_unique_id(&mut uid, true);
+    });
+    let id = id_from_seed(u64::from_le_bytes(uid));
+
+    let mut watchdog = Watchdog::new(pac.WATCHDOG);
+
+    let clocks = rp2040_hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let timer = RefCell::new(FugitTimer(Timer::new(
+        pac.TIMER,
+        &mut pac.RESETS,
+        &clocks,
+    )));
+
+    let sio = Sio::new(pac.SIO);
+    let pins = Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+    let mut delay = cortex_m::delay::Delay::new(
+        core.SYST,
+        clocks.system_clock.freq().to_Hz(),
+    );
+
+    let mut transmitter = ZapMe::builder()
+        .pin(pins.gpio16.into_push_pull_output())
+        .delay(&timer)
+        .now_fn(timing::now_fn(&timer))
+        .id(id)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+
+    // Pair the collar with the derived ID by beeping while it is in pairing
+    // mode, then confirm the pairing with a short beep every 5 seconds
+    collar.beep_ms(2000);
+    loop {
+        delay.delay_ms(5000);
+        collar.beep_ms(250);
+    }
+}
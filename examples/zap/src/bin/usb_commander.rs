@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+use cortex_m_rt::entry;
+use panic_halt as _;
+use rp2040_hal::{
+    gpio::Pins, pac, sio::Sio, timer::Timer, usb::UsbBus, watchdog::Watchdog,
+};
+use usb_device::class_prelude::UsbBusAllocator;
+use usb_device::device::{StringDescriptors, UsbDeviceBuilder, UsbVidPid};
+use usbd_serial::SerialPort;
+
+// SAFETY: the boot loader has to be in the .boot2 section, which memory.x
+// places at the start of flash where the RP2040 boot ROM expects it
+#[unsafe(link_section = ".boot2")]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+use zap_me::ch8803::Transmitter as ZapMe;
+use zap_me::timing::{self, FugitTimer};
+use zap_me::usb_commander::UsbCommander;
+
+#[entry]
+fn main() -> ! {
+    let mut pac = pac::Peripherals::take().unwrap();
+
+    let mut watchdog = Watchdog::new(pac.WATCHDOG);
+
+    let clocks = rp2040_hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let timer = RefCell::new(FugitTimer(Timer::new(
+        pac.TIMER,
+        &mut pac.RESETS,
+        &clocks,
+    )));
+
+    let sio = Sio::new(pac.SIO);
+    let pins = Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+
+    let usb_bus = UsbBusAllocator::new(UsbBus::new(
+        pac.USBCTRL_REGS,
+        pac.USBCTRL_DPRAM,
+        clocks.usb_clock,
+        true,
+        &mut pac.RESETS,
+    ));
+    let serial = SerialPort::new(&usb_bus);
+    // Test VID/PID pair for CDC devices, see pid.codes
+    let device = UsbDeviceBuilder::new(&usb_bus, UsbVidPid(0x16c0, 0x27dd))
+        .strings(&[StringDescriptors::default()
+            .manufacturer("zap-me")
+            .product("CH8803 Commander")
+            .serial_number("0001")])
+        .unwrap()
+        .device_class(usbd_serial::USB_CLASS_CDC)
+        .build();
+    let mut commander = UsbCommander::new(device, serial);
+
+    let zap = pins.gpio16.into_push_pull_output();
+    let led = pins.gpio25.into_push_pull_output();
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&timer)
+        .now_fn(timing::now_fn(&timer))
+        .id(0x0D25)
+        .build()
+        .with_led(led);
+
+    // Connect with any serial terminal and enter e.g. `vibrate 1 50 500`
+    loop {
+        commander.poll(&mut transmitter);
+    }
+}
@@ -7,7 +7,10 @@ use panic_halt as _;
 use rp2040_hal::{
     Clock, gpio::Pins, pac, sio::Sio, timer::Timer, watchdog::Watchdog,
 };
+use rtt_target::{ChannelMode, rtt_init_print};
 
+// SAFETY: the boot loader has to be in the .boot2 section, which memory.x
+// places at the start of flash where the RP2040 boot ROM expects it
 #[unsafe(link_section = ".boot2")]
 #[used]
 pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
@@ -15,9 +18,14 @@ pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
 const XTAL_FREQ_HZ: u32 = 12_000_000u32;
 
 use zap_me::ch8803::Transmitter as ZapMe;
+use zap_me::timing::{self, FugitTimer};
 
 #[entry]
 fn main() -> ! {
+    // Every command is logged over RTT, see Embed.toml. The buffer holds
+    // about 20 log lines, more is dropped if the host does not keep up.
+    rtt_init_print!(ChannelMode::NoBlockSkip, 1024);
+
     let mut pac = pac::Peripherals::take().unwrap();
     let core = pac::CorePeripherals::take().unwrap();
 
@@ -35,7 +43,11 @@ fn main() -> ! {
     .ok()
     .unwrap();
 
-    let timer = RefCell::new(Timer::new(pac.TIMER, &mut pac.RESETS, &clocks));
+    let timer = RefCell::new(FugitTimer(Timer::new(
+        pac.TIMER,
+        &mut pac.RESETS,
+        &clocks,
+    )));
 
     let sio = Sio::new(pac.SIO);
     let pins = Pins::new(
@@ -50,14 +62,16 @@ fn main() -> ! {
     );
 
     let zap = pins.gpio16.into_push_pull_output();
+    let led = pins.gpio25.into_push_pull_output();
 
     let mut transmitter = ZapMe::builder()
         .pin(zap)
         .delay(&timer)
-        .now_fn(|| timer.borrow_mut().get_counter())
+        .now_fn(timing::now_fn(&timer))
         .id(0x0D25)
-        .build();
-    let mut collar = transmitter.channel(0);
+        .build()
+        .with_led(led);
+    let mut collar = transmitter.channel(0).unwrap();
 
     loop {
         // Send weakest and strongest vibration pulse for 2 seconds each
@@ -0,0 +1,145 @@
+//! The `zap` example hardened against stack overflows.
+//!
+//! Without further measures, the stack of a Cortex-M grows down towards the
+//! static variables and overwrites them on overflow, which shows up as
+//! random misbehavior. Linking with `flip-link` (see `.cargo/config.toml`)
+//! moves the stack to the bottom of RAM, so that an overflow runs off the
+//! start of RAM and faults immediately.
+//!
+//! # Stack usage
+//!
+//! Every command encodes its packet into a buffer on the stack of
+//! `MAX_TIMINGS` (300) entries of `Timing`. This example enables the
+//! `u16-timing` feature, which halves the buffer to 600 bytes compared to
+//! the default `u32` timings. Some methods need several buffers:
+//! `send_hopping` keeps three and `send_batch` four, so prefer the
+//! commands of `ChannelTransmitter` on small stacks.
+//!
+//! The buffers are only a lower bound. To find the actual requirement, this
+//! example paints the free stack with a known pattern at startup and reports
+//! the high-water mark, the deepest point the stack reached, over RTT after
+//! every round of commands. Give the stack at least that much plus a safety
+//! margin for interrupt handlers.
+//!
+//! The `paint-stack` feature of `cortex-m-rt` cannot be used with
+//! `flip-link`, since it only paints the RAM above the static variables.
+
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+use core::ptr;
+use cortex_m_rt::entry;
+use panic_halt as _;
+use rp2040_hal::{
+    Clock, gpio::Pins, pac, sio::Sio, timer::Timer, watchdog::Watchdog,
+};
+use rtt_target::{rprintln, rtt_init_print};
+
+// SAFETY: the boot loader has to be in the .boot2 section, which memory.x
+// places at the start of flash where the RP2040 boot ROM expects it
+#[unsafe(link_section = ".boot2")]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+/// The bottom of the stack, which flip-link places at the start of RAM.
+const STACK_BOTTOM: *mut u32 = 0x2000_0000 as *mut u32;
+/// The value the free stack is filled with, as used by `cortex-m-rt`.
+const PAINT: u32 = 0xCCCC_CCCC;
+/// The part of the stack below the current frame that is left unpainted.
+const PAINT_MARGIN: usize = 256;
+
+unsafe extern "C" {
+    /// The top of the stack, set by flip-link.
+    static _stack_start: u32;
+}
+
+use zap_me::ch8803::Transmitter as ZapMe;
+use zap_me::timing::{self, FugitTimer};
+
+#[entry]
+fn main() -> ! {
+    paint_stack();
+    rtt_init_print!();
+
+    let mut pac = pac::Peripherals::take().unwrap();
+    let core = pac::CorePeripherals::take().unwrap();
+
+    let mut watchdog = Watchdog::new(pac.WATCHDOG);
+
+    let clocks = rp2040_hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let timer = RefCell::new(FugitTimer(Timer::new(
+        pac.TIMER,
+        &mut pac.RESETS,
+        &clocks,
+    )));
+
+    let sio = Sio::new(pac.SIO);
+    let pins = Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+    let mut delay = cortex_m::delay::Delay::new(
+        core.SYST,
+        clocks.system_clock.freq().to_Hz(),
+    );
+
+    let zap = pins.gpio16.into_push_pull_output();
+
+    let mut transmitter = ZapMe::builder()
+        .pin(zap)
+        .delay(&timer)
+        .now_fn(timing::now_fn(&timer))
+        .id(0x0D25)
+        .build();
+    let mut collar = transmitter.channel(0).unwrap();
+
+    loop {
+        collar.vibrate_ms(1, 2000);
+        collar.beep_ms(250);
+        rprintln!("stack high-water mark: {} bytes", stack_high_water());
+        delay.delay_ms(5000);
+    }
+}
+
+/// Fills the unused stack below the current frame with [PAINT].
+fn paint_stack() {
+    let sp = cortex_m::register::msp::read() as usize;
+    let mut word = STACK_BOTTOM;
+    while (word as usize) < sp - PAINT_MARGIN {
+        // SAFETY: the words between the bottom of the stack and the current
+        // frame are not in use yet
+        unsafe {
+            ptr::write_volatile(word, PAINT);
+            word = word.add(1);
+        }
+    }
+}
+
+/// The most stack that was used since [paint_stack], in bytes.
+fn stack_high_water() -> usize {
+    let top = &raw const _stack_start as usize;
+    let mut word = STACK_BOTTOM;
+    // SAFETY: all words between the bottom and the top of the stack are RAM
+    unsafe {
+        while (word as usize) < top && ptr::read_volatile(word) == PAINT {
+            word = word.add(1);
+        }
+    }
+    top - word as usize
+}
@@ -0,0 +1,9 @@
+#!/bin/sh
+# Installs Miri and runs the library tests under it, which detects undefined
+# behavior in unsafe code. Only the host-side features are enabled, as Miri
+# cannot run the hardware backends or the examples.
+set -e
+
+rustup toolchain install nightly --component miri,rust-src
+cargo +nightly miri setup
+cargo +nightly miri test --target x86_64-unknown-linux-gnu --features std,ffi,simulator,diagnostics "$@"
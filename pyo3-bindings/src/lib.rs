@@ -0,0 +1,124 @@
+//! Python bindings for generating the timings of CH8803 packets, e.g. as
+//! reference for hardware captures or SDR recordings.
+//!
+//! ```python
+//! import zap_me
+//! timings = zap_me.encode_packet_py(0x0D25, zap_me.PyChannel.Channel1,
+//!                                   zap_me.PyCommand.Vibrate, 50)
+//! ```
+
+use pyo3::prelude::*;
+use zap_me::ch8803::{Channel, Command, CommandPacket, MAX_TIMINGS, Timing};
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PyChannel {
+    Channel1,
+    Channel2,
+    Channel3,
+}
+
+impl From<PyChannel> for Channel {
+    fn from(channel: PyChannel) -> Self {
+        match channel {
+            PyChannel::Channel1 => Channel::Channel1,
+            PyChannel::Channel2 => Channel::Channel2,
+            PyChannel::Channel3 => Channel::Channel3,
+        }
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PyCommand {
+    Shock,
+    Vibrate,
+    Beep,
+}
+
+impl From<PyCommand> for Command {
+    fn from(command: PyCommand) -> Self {
+        match command {
+            PyCommand::Shock => Command::Shock,
+            PyCommand::Vibrate => Command::Vibrate,
+            PyCommand::Beep => Command::Beep,
+        }
+    }
+}
+
+/// A single command addressed to a receiver.
+#[pyclass]
+#[derive(Clone, Copy)]
+pub struct PyCommandPacket {
+    #[pyo3(get, set)]
+    pub id: u16,
+    #[pyo3(get, set)]
+    pub channel: PyChannel,
+    #[pyo3(get, set)]
+    pub command: PyCommand,
+    #[pyo3(get, set)]
+    pub strength: u8,
+}
+
+#[pymethods]
+impl PyCommandPacket {
+    #[new]
+    fn new(
+        id: u16,
+        channel: PyChannel,
+        command: PyCommand,
+        strength: u8,
+    ) -> Self {
+        Self {
+            id,
+            channel,
+            command,
+            strength,
+        }
+    }
+
+    /// The timings of the packet in microseconds, alternating between low and
+    /// high and starting low. Zero entries keep the previous level.
+    fn timings(&self) -> Vec<Timing> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: self.channel.into(),
+            command: self.command.into(),
+            strength: self.strength,
+        };
+        let mut timings = [0; MAX_TIMINGS];
+        let len = packet.encode(&mut timings);
+        timings[..len].to_vec()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyCommandPacket(id={:#06x}, channel={:?}, command={:?}, strength={})",
+            self.id,
+            Channel::from(self.channel),
+            Command::from(self.command),
+            self.strength,
+        )
+    }
+}
+
+/// Returns the timings of a packet, see `PyCommandPacket.timings`.
+#[pyfunction]
+fn encode_packet_py(
+    id: u16,
+    channel: PyChannel,
+    command: PyCommand,
+    strength: u8,
+) -> Vec<Timing> {
+    PyCommandPacket::new(id, channel, command, strength).timings()
+}
+
+#[pymodule]
+#[pyo3(name = "zap_me")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyChannel>()?;
+    module.add_class::<PyCommand>()?;
+    module.add_class::<PyCommandPacket>()?;
+    module.add_function(wrap_pyfunction!(encode_packet_py, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! Sends commands without blocking an async executor, e.g. with embassy.
+//! Only the delay is awaited; the pin and `now_fn` are used synchronously.
+
+use crate::ch8803::{
+    Channel, Command, CommandPacket, DEFAULT_INTER_PACKET_GAP, Duration,
+    InstantFn, MAX_TIMINGS, TIMING_PER_US, Timing, TransmitError,
+    channel_commands,
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+/// Sends commands to CH8803 receivers, awaiting every pulse instead of
+/// blocking. Apart from the inter-packet gap, the minimum number of
+/// repetitions and the strength limit, which default to the same values,
+/// only the default settings of a [Transmitter](crate::ch8803::Transmitter)
+/// are supported.
+///
+/// The executor may run other tasks during the pulses, and a pulse that is
+/// resumed late distorts the packet, so the receiver might ignore single
+/// repetitions. Keep other tasks short while a command is sent.
+pub struct AsyncTransmitter<PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: DelayNs,
+    NOW: InstantFn,
+{
+    pin: PIN,
+    delay: DELAY,
+    now_fn: NOW,
+    id: u16,
+    min_repeats: u8,
+    inter_packet_gap: Duration,
+    max_strength: Option<u8>,
+}
+
+impl<PIN, DELAY, NOW> AsyncTransmitter<PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: DelayNs,
+    NOW: InstantFn,
+{
+    /// Creates a transmitter with the given ID.
+    pub fn new(pin: PIN, delay: DELAY, now_fn: NOW, id: u16) -> Self {
+        Self {
+            pin,
+            delay,
+            now_fn,
+            id,
+            min_repeats: 1,
+            inter_packet_gap: DEFAULT_INTER_PACKET_GAP,
+            max_strength: None,
+        }
+    }
+
+    /// Returns the pin and the delay.
+    pub fn into_parts(self) -> (PIN, DELAY) {
+        (self.pin, self.delay)
+    }
+
+    /// The ID of the device.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Sends at least `min_repeats` repetitions of every packet, see
+    /// [Transmitter::set_min_repeats](crate::ch8803::Transmitter::set_min_repeats).
+    pub fn set_min_repeats(&mut self, min_repeats: u8) {
+        self.min_repeats = min_repeats;
+    }
+
+    /// Holds the pin low for `gap` after every repetition of a packet, see
+    /// [Transmitter::set_inter_packet_gap](crate::ch8803::Transmitter::set_inter_packet_gap).
+    pub fn set_inter_packet_gap(&mut self, gap: Duration) {
+        self.inter_packet_gap = gap;
+    }
+
+    /// Limits the strength of every command sent to `cap`, see
+    /// [Transmitter::set_max_strength](crate::ch8803::Transmitter::set_max_strength).
+    pub fn set_max_strength(&mut self, cap: u8) {
+        self.max_strength = Some(cap);
+    }
+
+    /// Removes the limit set with [set_max_strength](Self::set_max_strength).
+    pub fn clear_max_strength(&mut self) {
+        self.max_strength = None;
+    }
+
+    /// Binds the transmitter to a specific channel, see
+    /// [Transmitter::channel](crate::ch8803::Transmitter::channel).
+    pub fn channel<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+    ) -> Result<AsyncChannelTransmitter<'_, PIN, DELAY, NOW>, C::Error> {
+        Ok(AsyncChannelTransmitter {
+            device: self,
+            channel: channel.try_into()?,
+        })
+    }
+
+    /// Sends a command for the given duration and returns the number of
+    /// packets sent without errors, or the error of the channel conversion,
+    /// see [Transmitter::send_command](crate::ch8803::Transmitter::send_command).
+    pub async fn send_command<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> Result<u32, C::Error> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: channel.try_into()?,
+            command,
+            strength,
+        };
+        Ok(self.send_packet(&packet, duration).await)
+    }
+
+    /// Sends a packet for the given duration, using the ID stored in the
+    /// packet, and returns the number of packets sent without errors.
+    pub async fn send_packet(
+        &mut self,
+        packet: &CommandPacket,
+        duration: impl Into<Duration>,
+    ) -> u32 {
+        let mut packet = *packet;
+        if let Some(cap) = self.max_strength
+            && packet.command != Command::Beep
+        {
+            packet.strength = packet.strength.min(cap);
+        }
+        let mut timings = [0; MAX_TIMINGS];
+        let len = packet.encode(&mut timings);
+
+        let mut repetitions = 0;
+        let mut sent = 0;
+        let end = (self.now_fn)() + duration.into();
+        while repetitions < u32::from(self.min_repeats) || (self.now_fn)() < end
+        {
+            if self.send_timing(&timings[..len]).await.is_ok() {
+                sent += 1;
+            }
+            repetitions += 1;
+            // send_timing leaves the pin low
+            self.delay.delay_us(self.inter_packet_gap.to_micros()).await;
+        }
+        sent
+    }
+
+    async fn send_timing(
+        &mut self,
+        timings: &[Timing],
+    ) -> Result<(), TransmitError> {
+        let mut result = Ok(());
+        let mut level = false;
+        for &duration in timings {
+            if duration == 0 {
+                level = !level;
+                continue;
+            }
+
+            let set = if level {
+                self.pin.set_high()
+            } else {
+                self.pin.set_low()
+            };
+            if set.is_err() {
+                result = Err(TransmitError::Pin);
+            }
+            let ns = u64::from(duration) * 1000 / TIMING_PER_US;
+            self.delay.delay_ns(ns as u32).await;
+            level = !level;
+        }
+        if self.pin.set_low().is_err() {
+            result = Err(TransmitError::Pin);
+        }
+        result
+    }
+}
+
+/// An [AsyncTransmitter] bound to a channel, the async counterpart of a
+/// [ChannelTransmitter](crate::ch8803::ChannelTransmitter).
+pub struct AsyncChannelTransmitter<'b, PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: DelayNs,
+    NOW: InstantFn,
+{
+    device: &'b mut AsyncTransmitter<PIN, DELAY, NOW>,
+    channel: Channel,
+}
+
+impl<PIN, DELAY, NOW> AsyncChannelTransmitter<'_, PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: DelayNs,
+    NOW: InstantFn,
+{
+    channel_commands!(async);
+
+    async fn send(
+        &mut self,
+        command: Command,
+        strength: u8,
+        duration: Duration,
+    ) {
+        let Ok(_) = self
+            .device
+            .send_command(self.channel, command, strength, duration)
+            .await;
+    }
+}
@@ -0,0 +1,200 @@
+//! Forwarding commands received over Bluetooth LE, e.g. from a smartphone
+//! app, to a [Transmitter] on an nRF52.
+//!
+//! The bridge is independent of the BLE stack. It expects the Nordic UART
+//! Service (NUS), whose UUIDs are given below, and only handles the data
+//! written to its RX characteristic: call [BleBridge::handle_write] from
+//! the write handler and notify the returned status code on the TX
+//! characteristic.
+//!
+//! # Pairing
+//!
+//! Anyone in range can write to an unprotected characteristic, so the RX
+//! characteristic should require an encrypted, authenticated link. Use
+//! LE Secure Connections with passkey entry or numeric comparison, store the
+//! bond, and stop advertising as connectable to unknown devices once the app
+//! has bonded. "Just Works" pairing offers no protection against a
+//! man-in-the-middle and should not be used for commands that shock.
+//!
+//! # SoftDevice
+//!
+//! With Nordic's SoftDevice (e.g. through the `nrf-softdevice` crate), the
+//! write event is delivered from the SoftDevice event handler. Sending a
+//! command blocks for its whole duration, so the handler should only copy
+//! the data into a queue and a separate task should call
+//! [handle_write](BleBridge::handle_write). The 433 MHz module on the data
+//! pin is independent of the nRF52 radio, so no timeslot has to be
+//! requested, but SoftDevice interrupts preempt the transmission and delay
+//! pin changes. The `WaveformRecorder` of the `diagnostics` feature shows
+//! whether the timing is still good enough.
+
+use crate::ch8803::{
+    Channel, Command, CommandPacket, Duration, InstantFn, Transmitter,
+};
+use crate::encoder::Encoder;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+
+/// The UUID of the Nordic UART Service.
+pub const NUS_SERVICE_UUID: &str = "6E400001-B5A3-F393-E0A9-E50E24DCCA9E";
+/// The characteristic the app writes commands to.
+pub const NUS_RX_UUID: &str = "6E400002-B5A3-F393-E0A9-E50E24DCCA9E";
+/// The characteristic the status of every write is notified on.
+pub const NUS_TX_UUID: &str = "6E400003-B5A3-F393-E0A9-E50E24DCCA9E";
+
+/// The length of a frame: the ID (big endian), the channel (0–2), the
+/// command (1 = shock, 2 = vibrate, 3 = beep), the strength and the duration
+/// in milliseconds (big endian).
+pub const FRAME_LEN: usize = 7;
+
+/// The reason a write was rejected. Nothing is sent for rejected writes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BleError {
+    /// The length of the write is not a multiple of [FRAME_LEN].
+    Length,
+    /// A frame contains an unknown channel.
+    Channel,
+    /// A frame contains an unknown command.
+    Command,
+}
+
+impl BleError {
+    /// The status code to notify on the TX characteristic.
+    pub fn code(self) -> u8 {
+        match self {
+            BleError::Length => 1,
+            BleError::Channel => 2,
+            BleError::Command => 3,
+        }
+    }
+}
+
+/// Dispatches frames written to the NUS RX characteristic.
+#[derive(Clone, Copy, Default)]
+pub struct BleBridge {
+    max_duration_ms: Option<u16>,
+}
+
+impl BleBridge {
+    pub const fn new() -> Self {
+        Self {
+            max_duration_ms: None,
+        }
+    }
+
+    /// Limits the duration of every command, regardless of what the app
+    /// requests.
+    pub const fn with_max_duration(max_duration_ms: u16) -> Self {
+        Self {
+            max_duration_ms: Some(max_duration_ms),
+        }
+    }
+
+    /// Decodes a single frame.
+    pub fn decode(
+        frame: &[u8; FRAME_LEN],
+    ) -> Result<(CommandPacket, u16), BleError> {
+        let channel =
+            Channel::try_from(frame[2]).map_err(|_| BleError::Channel)?;
+        let command =
+            Command::try_from(frame[3]).map_err(|_| BleError::Command)?;
+        let packet = CommandPacket {
+            id: u16::from_be_bytes([frame[0], frame[1]]),
+            channel,
+            command,
+            strength: frame[4],
+        };
+        Ok((packet, u16::from_be_bytes([frame[5], frame[6]])))
+    }
+
+    /// Sends the commands of all frames in `data`, one after another. The
+    /// whole write is rejected if any frame is invalid. Returns the number of
+    /// commands sent.
+    pub fn handle_write<PIN, DELAY, NOW, ENC, LED, BUSY>(
+        &self,
+        data: &[u8],
+        tx: &mut Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) -> Result<usize, BleError>
+    where
+        PIN: OutputPin,
+        DELAY: PulseDelay,
+        NOW: InstantFn,
+        ENC: Encoder,
+        LED: OutputPin,
+        BUSY: OutputPin,
+    {
+        if !data.len().is_multiple_of(FRAME_LEN) {
+            return Err(BleError::Length);
+        }
+        let frames = data.chunks_exact(FRAME_LEN).map(|frame| {
+            Self::decode(frame.try_into().expect("chunks have FRAME_LEN"))
+        });
+        if let Some(Err(err)) = frames.clone().find(Result::is_err) {
+            return Err(err);
+        }
+
+        for (packet, duration_ms) in frames.flatten() {
+            let duration_ms = match self.max_duration_ms {
+                Some(max) => duration_ms.min(max),
+                None => duration_ms,
+            };
+            tx.send_packet(&packet, Duration::millis(duration_ms.into()));
+        }
+        Ok(data.len() / FRAME_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::NoPin;
+    use crate::timing::{self, MockTimingSource, TimingSource};
+    use core::cell::RefCell;
+
+    /// ID 0x0D25, channel 2, vibrate at 50 for 500 ms.
+    const FRAME: [u8; FRAME_LEN] = [0x0d, 0x25, 1, 2, 50, 0x01, 0xf4];
+
+    #[test]
+    fn decodes_frames() {
+        let packet = CommandPacket {
+            id: 0x0d25,
+            channel: Channel::Channel2,
+            command: Command::Vibrate,
+            strength: 50,
+        };
+        assert_eq!(BleBridge::decode(&FRAME), Ok((packet, 500)));
+
+        let mut frame = FRAME;
+        frame[2] = 3;
+        assert_eq!(BleBridge::decode(&frame), Err(BleError::Channel));
+        for command in [0, 4] {
+            let mut frame = FRAME;
+            frame[3] = command;
+            assert_eq!(BleBridge::decode(&frame), Err(BleError::Command));
+        }
+    }
+
+    #[test]
+    fn handles_writes() {
+        let source = RefCell::new(MockTimingSource::default());
+        let mut tx = timing::transmitter(NoPin, &source, 1);
+        let bridge = BleBridge::new();
+
+        let mut data = [0; 2 * FRAME_LEN];
+        data[..FRAME_LEN].copy_from_slice(&FRAME);
+        data[FRAME_LEN..].copy_from_slice(&FRAME);
+        assert_eq!(bridge.handle_write(&data, &mut tx), Ok(2));
+        assert_eq!(bridge.handle_write(&[], &mut tx), Ok(0));
+        assert_eq!(
+            bridge.handle_write(&data[1..], &mut tx),
+            Err(BleError::Length)
+        );
+
+        // A single invalid frame rejects the whole write, nothing is sent
+        data[FRAME_LEN + 3] = 9;
+        let before = source.borrow().now();
+        assert_eq!(bridge.handle_write(&data, &mut tx), Err(BleError::Command));
+        assert_eq!(source.borrow().now(), before);
+    }
+}
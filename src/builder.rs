@@ -0,0 +1,308 @@
+use crate::ch8803::{
+    DEFAULT_FOOTER, DEFAULT_INTER_PACKET_GAP, DEFAULT_PREAMBLE, Duration,
+    InstantFn, MAX_FOOTER, MAX_PREAMBLE, ProtocolTiming, TimingSequence,
+    Transmitter, TransmitterConfig, preamble_sequence,
+};
+use crate::fec::Fec;
+use crate::timing::PulseDelay;
+use crate::whitening::DataWhitening;
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use embedded_hal::digital::OutputPin;
+
+/// The first stage of a [TransmitterBuilder], waiting for the pin.
+pub struct NeedPin;
+/// The stage of a [TransmitterBuilder] waiting for the delay.
+pub struct NeedDelay<PIN>(PIN);
+/// The stage of a [TransmitterBuilder] waiting for the `now_fn`.
+pub struct NeedNowFn<'a, PIN, DELAY> {
+    pin: PIN,
+    delay: &'a RefCell<DELAY>,
+}
+/// The stage of a [TransmitterBuilder] waiting for the ID.
+pub struct NeedId<'a, PIN, DELAY, NOW> {
+    pin: PIN,
+    delay: &'a RefCell<DELAY>,
+    now_fn: NOW,
+}
+/// The last stage of a [TransmitterBuilder], where all settings are given.
+pub struct Ready<'a, PIN, DELAY, NOW> {
+    pin: PIN,
+    delay: &'a RefCell<DELAY>,
+    now_fn: NOW,
+    id: u16,
+}
+
+/// Builder returned by [Transmitter::builder]. Every setter moves the
+/// builder to the next `STAGE`, which holds the settings given so far, and
+/// only the [Ready] stage can be built, so forgetting a setting is a compile
+/// error.
+pub struct TransmitterBuilder<'a, STAGE, PIN, DELAY, NOW> {
+    stage: STAGE,
+    _types: PhantomData<(PIN, &'a RefCell<DELAY>, NOW)>,
+    whitening: Option<DataWhitening>,
+    fec: Fec,
+    tx_jitter_us: u16,
+    inter_bit_gap_us: u16,
+    min_repeats: u8,
+    max_repeats: Option<u8>,
+    inter_packet_gap: Duration,
+    preamble: TimingSequence<MAX_PREAMBLE>,
+    footer: TimingSequence<MAX_FOOTER>,
+    protocol_timing: ProtocolTiming,
+    max_strength: Option<u8>,
+}
+
+impl<'a, PIN, DELAY, NOW> TransmitterBuilder<'a, NeedPin, PIN, DELAY, NOW> {
+    pub(crate) fn new() -> Self {
+        TransmitterBuilder {
+            stage: NeedPin,
+            _types: PhantomData,
+            whitening: None,
+            fec: Fec::None,
+            tx_jitter_us: 0,
+            inter_bit_gap_us: 0,
+            min_repeats: 1,
+            max_repeats: None,
+            inter_packet_gap: DEFAULT_INTER_PACKET_GAP,
+            preamble: DEFAULT_PREAMBLE,
+            footer: DEFAULT_FOOTER,
+            protocol_timing: ProtocolTiming::ch8803_default(),
+            max_strength: None,
+        }
+    }
+}
+
+impl<'a, STAGE, PIN, DELAY, NOW>
+    TransmitterBuilder<'a, STAGE, PIN, DELAY, NOW>
+{
+    /// Moves the builder to the next stage, keeping all settings.
+    pub(crate) fn stage<S>(
+        self,
+        stage: S,
+    ) -> TransmitterBuilder<'a, S, PIN, DELAY, NOW> {
+        self.replace_stage(stage).1
+    }
+
+    /// Like [stage](Self::stage), but also returns the data of the old stage.
+    pub(crate) fn replace_stage<S>(
+        self,
+        stage: S,
+    ) -> (STAGE, TransmitterBuilder<'a, S, PIN, DELAY, NOW>) {
+        let builder = TransmitterBuilder {
+            stage,
+            _types: PhantomData,
+            whitening: self.whitening,
+            fec: self.fec,
+            tx_jitter_us: self.tx_jitter_us,
+            inter_bit_gap_us: self.inter_bit_gap_us,
+            min_repeats: self.min_repeats,
+            max_repeats: self.max_repeats,
+            inter_packet_gap: self.inter_packet_gap,
+            preamble: self.preamble,
+            footer: self.footer,
+            protocol_timing: self.protocol_timing,
+            max_strength: self.max_strength,
+        };
+        (self.stage, builder)
+    }
+
+    /// Scrambles the payload of command packets, for receivers that expect
+    /// whitened data. This can be set in any stage.
+    pub fn whitening(mut self, whitening: DataWhitening) -> Self {
+        self.whitening = Some(whitening);
+        self
+    }
+
+    /// Adds error correction to the payload of command packets. This can be
+    /// set in any stage.
+    pub fn fec(mut self, fec: Fec) -> Self {
+        self.fec = fec;
+        self
+    }
+
+    /// Adds a random delay of up to `max_jitter_us` before every repetition,
+    /// see [Transmitter::set_tx_jitter]. This can be set in any stage.
+    pub fn tx_jitter(mut self, max_jitter_us: u16) -> Self {
+        self.tx_jitter_us = max_jitter_us;
+        self
+    }
+
+    /// Holds every level for `us` additional microseconds, see
+    /// [Transmitter::set_inter_bit_gap]. This can be set in any stage.
+    pub fn inter_bit_gap(mut self, us: u16) -> Self {
+        self.inter_bit_gap_us = us;
+        self
+    }
+
+    /// Sends at least `min_repeats` repetitions of every packet, see
+    /// [Transmitter::set_min_repeats]. This can be set in any stage.
+    pub fn min_repeats(mut self, min_repeats: u8) -> Self {
+        self.min_repeats = min_repeats;
+        self
+    }
+
+    /// Sends at most `max_repeats` repetitions of every packet, see
+    /// [Transmitter::set_max_repeats]. This can be set in any stage.
+    pub fn max_repeats(mut self, max_repeats: u8) -> Self {
+        self.max_repeats = Some(max_repeats);
+        self
+    }
+
+    /// Holds the pin low for `gap` after every repetition, see
+    /// [Transmitter::set_inter_packet_gap]. Defaults to 10 ms, and zero
+    /// disables it. This can be set in any stage.
+    pub fn inter_packet_gap(mut self, gap: Duration) -> Self {
+        self.inter_packet_gap = gap;
+        self
+    }
+
+    /// Replaces the preamble sent before every packet, see
+    /// [Transmitter::set_preamble]. This can be set in any stage.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [Transmitter::set_preamble].
+    pub fn preamble(mut self, preamble: &[u16]) -> Self {
+        self.preamble = preamble_sequence(preamble);
+        self
+    }
+
+    /// Replaces the footer sent after every packet, see
+    /// [Transmitter::set_footer]. This can be set in any stage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `footer` has more than [MAX_FOOTER] entries.
+    pub fn footer(mut self, footer: &[u16]) -> Self {
+        self.footer = TimingSequence::new(footer);
+        self
+    }
+
+    /// Limits the strength of every command to `cap`, see
+    /// [Transmitter::set_max_strength]. This can be set in any stage.
+    pub fn max_strength(mut self, cap: u8) -> Self {
+        self.max_strength = Some(cap);
+        self
+    }
+
+    /// Replaces the pulse lengths of the bits and the preamble and footer,
+    /// see [Transmitter::set_protocol_timing]. A preamble or footer given
+    /// afterwards takes precedence. This can be set in any stage.
+    pub fn timing(mut self, timing: ProtocolTiming) -> Self {
+        self.protocol_timing = timing;
+        self.preamble = timing.preamble();
+        self.footer = timing.footer();
+        self
+    }
+}
+
+impl<'a, PIN, DELAY, NOW> TransmitterBuilder<'a, NeedPin, PIN, DELAY, NOW> {
+    /// Same as [pin](TransmitterBuilder::pin), for stages that assemble the
+    /// pin from several parts.
+    pub(crate) fn set_pin(
+        self,
+        pin: PIN,
+    ) -> TransmitterBuilder<'a, NeedDelay<PIN>, PIN, DELAY, NOW> {
+        self.stage(NeedDelay(pin))
+    }
+}
+
+impl<'a, PIN: OutputPin, DELAY, NOW>
+    TransmitterBuilder<'a, NeedPin, PIN, DELAY, NOW>
+{
+    /// The pin used to transmit the signal. This pin should be connected to
+    /// the DATA pin of a 433 MHz transmitter module.
+    pub fn pin(
+        self,
+        pin: PIN,
+    ) -> TransmitterBuilder<'a, NeedDelay<PIN>, PIN, DELAY, NOW> {
+        self.set_pin(pin)
+    }
+}
+
+impl<'a, PIN, DELAY: PulseDelay, NOW>
+    TransmitterBuilder<'a, NeedDelay<PIN>, PIN, DELAY, NOW>
+{
+    /// The delay implementation used to control the timing of the signal.
+    pub fn delay(
+        self,
+        delay: &'a RefCell<DELAY>,
+    ) -> TransmitterBuilder<'a, NeedNowFn<'a, PIN, DELAY>, PIN, DELAY, NOW>
+    {
+        let (NeedDelay(pin), builder) = self.replace_stage(());
+        builder.stage(NeedNowFn { pin, delay })
+    }
+}
+
+impl<'a, PIN, DELAY, NOW: InstantFn>
+    TransmitterBuilder<'a, NeedNowFn<'a, PIN, DELAY>, PIN, DELAY, NOW>
+{
+    /// A function that returns the current ticks.
+    pub fn now_fn(
+        self,
+        now_fn: NOW,
+    ) -> TransmitterBuilder<'a, NeedId<'a, PIN, DELAY, NOW>, PIN, DELAY, NOW>
+    {
+        let (NeedNowFn { pin, delay }, builder) = self.replace_stage(());
+        builder.stage(NeedId { pin, delay, now_fn })
+    }
+}
+
+impl<'a, PIN, DELAY, NOW>
+    TransmitterBuilder<'a, NeedId<'a, PIN, DELAY, NOW>, PIN, DELAY, NOW>
+{
+    /// The ID of the device. This should be a unique identifier for the
+    /// transmitter and is used together with the channel to pair a receiver.
+    pub fn id(
+        self,
+        id: u16,
+    ) -> TransmitterBuilder<'a, Ready<'a, PIN, DELAY, NOW>, PIN, DELAY, NOW>
+    {
+        let (NeedId { pin, delay, now_fn }, builder) = self.replace_stage(());
+        builder.stage(Ready {
+            pin,
+            delay,
+            now_fn,
+            id,
+        })
+    }
+}
+
+impl<'a, PIN, DELAY, NOW>
+    TransmitterBuilder<'a, Ready<'a, PIN, DELAY, NOW>, PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+{
+    /// Creates the Transmitter.
+    pub fn build(self) -> Transmitter<'a, PIN, DELAY, NOW> {
+        let Ready {
+            pin,
+            delay,
+            now_fn,
+            id,
+        } = self.stage;
+        let mut transmitter = Transmitter::from_config(TransmitterConfig {
+            pin,
+            delay,
+            now_fn,
+            id,
+        });
+        transmitter.set_whitening(self.whitening);
+        transmitter.set_fec(self.fec);
+        transmitter.set_tx_jitter(self.tx_jitter_us);
+        transmitter.set_inter_bit_gap(self.inter_bit_gap_us);
+        transmitter.set_min_repeats(self.min_repeats);
+        transmitter.set_max_repeats(self.max_repeats);
+        transmitter.set_inter_packet_gap(self.inter_packet_gap);
+        transmitter.set_protocol_timing(self.protocol_timing);
+        if let Some(cap) = self.max_strength {
+            transmitter.set_max_strength(cap);
+        }
+        transmitter.set_preamble(self.preamble.as_slice());
+        transmitter.set_footer(self.footer.as_slice());
+        transmitter
+    }
+}
@@ -0,0 +1,253 @@
+use crate::ch8803::{
+    CommandPacket, Duration, Instant, InstantFn, ONE_LEN, TIMING_PER_US,
+    Timing, ZERO_LEN,
+};
+use embedded_hal::digital::InputPin;
+
+/// Packets are 42 bits long, including the checksum and two padding bits.
+pub(crate) const PACKET_BITS: u8 = 42;
+
+/// The edges of a signal recorded by
+/// [sniff_mode](crate::ch8803::Transmitter::sniff_mode), holding at most `N`
+/// edges.
+pub struct SignalCapture<const N: usize> {
+    pub(crate) idle_timeout: Duration,
+    pub(crate) initial_level: bool,
+    edges: [Instant; N],
+    len: usize,
+}
+
+impl<const N: usize> SignalCapture<N> {
+    /// Creates an empty capture. Recording stops once the input has not
+    /// changed for `idle_timeout`.
+    pub const fn new(idle_timeout: Duration) -> Self {
+        Self {
+            idle_timeout,
+            initial_level: false,
+            edges: [Instant::from_ticks(0); N],
+            len: 0,
+        }
+    }
+
+    /// The level of the input before the first edge.
+    pub fn initial_level(&self) -> bool {
+        self.initial_level
+    }
+
+    /// The times at which the level of the input changed.
+    pub fn edges(&self) -> &[Instant] {
+        &self.edges[..self.len]
+    }
+
+    /// The pulses between the edges, as the level and the length in
+    /// microseconds.
+    pub fn pulses(&self) -> impl Iterator<Item = (bool, u64)> + '_ {
+        self.edges().windows(2).enumerate().map(|(i, edges)| {
+            let level = self.initial_level ^ (i % 2 == 0);
+            (level, (edges[1] - edges[0]).to_micros())
+        })
+    }
+
+    /// Writes the pulses into `timings` in the format sent by the
+    /// [Transmitter](crate::ch8803::Transmitter), starting with the low
+    /// level, and returns the number of entries. Pulses too long for a
+    /// [Timing] are truncated.
+    pub fn to_timings(&self, timings: &mut [Timing; N]) -> usize {
+        let mut idx = 0;
+        for (level, us) in self.pulses() {
+            // Timings start low, so an initial high pulse needs a skipped entry
+            if idx == 0 && level {
+                timings[0] = 0;
+                idx = 1;
+            }
+            let ticks = us.saturating_mul(TIMING_PER_US);
+            timings[idx] = Timing::try_from(ticks).unwrap_or(Timing::MAX);
+            idx += 1;
+        }
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Forgets all recorded edges.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub(crate) fn push(&mut self, edge: Instant) {
+        self.edges[self.len] = edge;
+        self.len += 1;
+    }
+}
+
+/// The reason [RawCapture::capture_frame] returned no frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CaptureError<E> {
+    /// Reading the input pin failed.
+    Pin(E),
+    /// No falling edge started a frame within the timeout.
+    Timeout,
+    /// The frame has more pulses than the buffer holds. The pulses
+    /// recorded so far are lost.
+    BufferFull,
+}
+
+/// Records the pulse widths of a single frame of an unknown protocol, e.g.
+/// from a receiver module picking up the original remote, to replay it
+/// with [send_timing_raw](crate::ch8803::Transmitter::send_timing_raw).
+/// Holds at most `N` pulses.
+pub struct RawCapture<IP: InputPin, NOW: InstantFn, const N: usize> {
+    pin: IP,
+    now_fn: NOW,
+    timeout: Duration,
+    widths: [u16; N],
+    len: usize,
+}
+
+impl<IP: InputPin, NOW: InstantFn, const N: usize> RawCapture<IP, NOW, N> {
+    /// Creates a capture polling `pin`, taking timestamps from `now_fn`. A
+    /// frame has to start within `timeout` and ends once the input has not
+    /// changed for `timeout`.
+    pub fn new(pin: IP, now_fn: NOW, timeout: Duration) -> Self {
+        Self {
+            pin,
+            now_fn,
+            timeout,
+            widths: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the pin.
+    pub fn into_inner(self) -> IP {
+        self.pin
+    }
+
+    /// The pulse widths of the last frame, empty if capturing failed.
+    pub fn widths(&self) -> &[u16] {
+        &self.widths[..self.len]
+    }
+
+    /// Waits for a falling edge and records the widths of the following
+    /// pulses in microseconds, alternating low and high and starting low,
+    /// until the input stays idle for the timeout. The idle level at the
+    /// end is not recorded. Pulses longer than `u16::MAX` µs are truncated.
+    ///
+    /// Like [sniff_mode](crate::ch8803::Transmitter::sniff_mode), polling
+    /// adds jitter of a few microseconds per edge.
+    pub fn capture_frame(&mut self) -> Result<&[u16], CaptureError<IP::Error>> {
+        self.len = 0;
+        let timeout = u64::from(self.timeout.to_micros());
+
+        let start = (self.now_fn)();
+        let mut level = self.pin.is_high().map_err(CaptureError::Pin)?;
+        let mut last_edge = loop {
+            let high = self.pin.is_high().map_err(CaptureError::Pin)?;
+            let now = (self.now_fn)();
+            if level && !high {
+                break now;
+            }
+            level = high;
+            if (now - start).to_micros() >= timeout {
+                return Err(CaptureError::Timeout);
+            }
+        };
+
+        let mut level = false;
+        loop {
+            let high = self.pin.is_high().map_err(CaptureError::Pin)?;
+            let now = (self.now_fn)();
+            let us = (now - last_edge).to_micros();
+            if high != level {
+                if self.len == N {
+                    self.len = 0;
+                    return Err(CaptureError::BufferFull);
+                }
+                self.widths[self.len] = u16::try_from(us).unwrap_or(u16::MAX);
+                self.len += 1;
+                level = high;
+                last_edge = now;
+            } else if us >= timeout {
+                return Ok(self.widths());
+            }
+        }
+    }
+}
+
+/// Decodes CH8803 packets from the lengths of high pulses. Only packets
+/// sent with the [PwmEncoder](crate::encoder::PwmEncoder) and without
+/// whitening or error correction are recognized.
+#[derive(Default)]
+pub struct PacketDecoder {
+    synced: bool,
+    bits: u64,
+    bit_count: u8,
+}
+
+impl PacketDecoder {
+    pub const fn new() -> Self {
+        Self {
+            synced: false,
+            bits: 0,
+            bit_count: 0,
+        }
+    }
+
+    /// Decodes the first valid packet in `capture`.
+    pub fn decode<const N: usize>(
+        capture: &SignalCapture<N>,
+    ) -> Option<CommandPacket> {
+        let mut decoder = Self::new();
+        capture
+            .pulses()
+            .filter(|&(level, _)| level)
+            .find_map(|(_, us)| decoder.high_pulse(us))
+    }
+
+    /// Whether a high pulse of `us` microseconds is the long pulse of the
+    /// preamble that starts a packet.
+    pub fn is_sync(us: u64) -> bool {
+        (1300..=1600).contains(&us)
+    }
+
+    /// Feeds the length of a high pulse in microseconds into the decoder.
+    /// Returns the packet once all of its bits have been received.
+    pub fn high_pulse(&mut self, us: u64) -> Option<CommandPacket> {
+        self.high_pulse_bits(us).and_then(CommandPacket::from_bits)
+    }
+
+    /// Like [high_pulse](Self::high_pulse), returning the raw bits of the
+    /// packet without decoding them.
+    pub(crate) fn high_pulse_bits(&mut self, us: u64) -> Option<u64> {
+        if Self::is_sync(us) {
+            *self = Self {
+                synced: true,
+                ..Self::new()
+            };
+            return None;
+        }
+        if !self.synced {
+            return None;
+        }
+
+        let threshold = u64::from(ZERO_LEN + ONE_LEN) / 2 / TIMING_PER_US;
+        self.bits = self.bits << 1 | u64::from(us > threshold);
+        self.bit_count += 1;
+        if self.bit_count < PACKET_BITS {
+            return None;
+        }
+        self.synced = false;
+        Some(self.bits)
+    }
+}
@@ -0,0 +1,52 @@
+use crate::ch8803::CommandPacket;
+
+/// A fixed-capacity FIFO of command packets that needs neither an allocator
+/// nor further dependencies, e.g. to queue commands received in an interrupt
+/// until the main loop sends them.
+pub struct CircularCommandBuffer<const N: usize> {
+    slots: [Option<CommandPacket>; N],
+    head: usize,
+    tail: usize,
+}
+
+impl<const N: usize> CircularCommandBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; N],
+            head: 0,
+            tail: 0,
+        }
+    }
+
+    /// Appends a packet, returning `false` if the buffer is full.
+    pub fn push(&mut self, packet: CommandPacket) -> bool {
+        if self.is_full() {
+            return false;
+        }
+
+        self.slots[self.tail] = Some(packet);
+        self.tail = (self.tail + 1) % N;
+        true
+    }
+
+    /// Removes and returns the oldest packet.
+    pub fn pop(&mut self) -> Option<CommandPacket> {
+        let packet = self.slots.get_mut(self.head)?.take()?;
+        self.head = (self.head + 1) % N;
+        Some(packet)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.get(self.head).is_none_or(Option::is_none)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.get(self.tail).is_none_or(Option::is_some)
+    }
+}
+
+impl<const N: usize> Default for CircularCommandBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,195 +1,3010 @@
+use crate::builder::{NeedPin, TransmitterBuilder};
+use crate::capture::SignalCapture;
+use crate::channels::{AnyChannel, FixedChannel};
+use crate::encoder::{Encoder, PwmEncoder};
+use crate::fec::Fec;
+use crate::indicator::{LedIndicator, NoPin};
+use crate::inspector::{PacketInspector, Phase, no_inspection};
+use crate::model::CollarModel;
+use crate::pulse::PulseTrain;
+use crate::strength::AdaptiveStrengthPolicy;
+#[cfg(feature = "rtt-logging")]
+use crate::timing::DurationExt;
+use crate::timing::PulseDelay;
+use crate::waveform;
+use crate::whitening::DataWhitening;
+#[cfg(all(feature = "observer", feature = "alloc"))]
+use alloc::boxed::Box;
 use core::cell::RefCell;
-use embedded_hal::blocking::delay::DelayUs;
-use embedded_hal::digital::v2::OutputPin;
-use typed_builder::TypedBuilder;
+#[cfg(feature = "adc")]
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+use embedded_hal::digital::{InputPin, OutputPin};
+#[cfg(feature = "adc")]
+use embedded_hal_02::adc::{self, OneShot};
 
-const PULSE_LEN: u16 = 1016;
-const ZERO_LEN: u16 = 292;
-const ONE_LEN: u16 = 804;
+pub(crate) const PULSE_LEN: Timing = us(1016);
+pub(crate) const ZERO_LEN: Timing = us(292);
+pub(crate) const ONE_LEN: Timing = us(804);
 
 pub type Instant = fugit::Instant<u64, 1, 1_000_000>;
 pub type Duration = fugit::Duration<u32, 1, 1_000_000>;
 
-type TimingVec = heapless::Vec<u16, 128>;
+/// The length of a single pulse in microseconds. The `u16-timing` feature
+/// narrows this to `u16`, which halves the size of encoded packets and
+/// limits pulses to about 65 ms.
+#[cfg(not(any(feature = "u16-timing", feature = "nanosecond-timing")))]
+pub type Timing = u32;
+/// The length of a single pulse in microseconds, narrowed to `u16` by the
+/// `u16-timing` feature.
+#[cfg(all(feature = "u16-timing", not(feature = "nanosecond-timing")))]
+pub type Timing = u16;
+/// The length of a single pulse in nanoseconds, as selected by the
+/// `nanosecond-timing` feature. Nanoseconds do not fit into `u16`, so this
+/// takes precedence over `u16-timing` if both are enabled.
+#[cfg(feature = "nanosecond-timing")]
+pub type Timing = u32;
+
+/// The number of [Timing] units per microsecond.
+#[cfg(not(feature = "nanosecond-timing"))]
+pub(crate) const TIMING_PER_US: u64 = 1;
+/// The number of [Timing] units per microsecond.
+#[cfg(feature = "nanosecond-timing")]
+pub(crate) const TIMING_PER_US: u64 = 1000;
+
+/// Converts microseconds to nanoseconds.
+pub const fn us_to_ns(us: u16) -> u32 {
+    us as u32 * 1000
+}
+
+/// Converts microseconds to [Timing] units.
+pub(crate) const fn us(us: u16) -> Timing {
+    #[cfg(feature = "nanosecond-timing")]
+    return us_to_ns(us);
+    #[cfg(not(feature = "nanosecond-timing"))]
+    return us as Timing;
+}
+
+/// The maximum number of entries of a preamble set with
+/// [set_preamble](Transmitter::set_preamble).
+pub const MAX_PREAMBLE: usize = 8;
+/// The maximum number of entries of a footer set with
+/// [set_footer](Transmitter::set_footer).
+pub const MAX_FOOTER: usize = 4;
+/// The maximum number of timings of a packet: the preamble and footer plus a
+/// Hamming-coded packet of 72 bits with up to four entries per bit.
+pub const MAX_TIMINGS: usize = MAX_PREAMBLE + MAX_FOOTER + 72 * 4;
+/// The number of distinct packets a [BatchCache] keeps encoded.
+const BATCH_CACHE: usize = 4;
+// The last entry is the gap before the first bit, PULSE_LEN - ZERO_LEN
+const PREAMBLE: [u16; 3] = [840, 1440, 724];
+pub(crate) const DEFAULT_PREAMBLE: TimingSequence<MAX_PREAMBLE> =
+    TimingSequence::new(&PREAMBLE);
+// A final short pulse, ZERO_LEN, and the gap before the next packet
+const FOOTER: [u16; 2] = [292, 1476];
+pub(crate) const DEFAULT_FOOTER: TimingSequence<MAX_FOOTER> =
+    TimingSequence::new(&FOOTER);
+/// The pause between repetitions of a packet sent by original remotes.
+pub(crate) const DEFAULT_INTER_PACKET_GAP: Duration = Duration::millis(10);
+const BREATHE_STEPS: u32 = 16;
+const TRIANGLE_STEPS: u32 = 16;
+#[cfg(feature = "adc")]
+const ADC_MAX: u16 = 4095;
 
 pub trait InstantFn: Fn() -> Instant {}
 impl<F: Fn() -> Instant> InstantFn for F {}
 
+/// A function that is called with every packet repetition sent.
+#[cfg(all(feature = "observer", feature = "alloc"))]
+pub type PacketObserver = Box<dyn Fn(&CommandPacket)>;
+
+/// A function that is called with every packet repetition sent.
+#[cfg(all(feature = "observer", not(feature = "alloc")))]
+pub type PacketObserver = &'static dyn Fn(&CommandPacket);
+
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Channel {
     Channel1 = 0,
     Channel2 = 1,
     Channel3 = 2,
 }
 
-impl From<u8> for Channel {
-    fn from(value: u8) -> Self {
+/// The reason a value is not a valid [Channel].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChannelError {
+    /// The index is not 0, 1 or 2.
+    OutOfRange,
+}
+
+/// Converts the index of a channel, 0–2, e.g. from user input or a config
+/// byte.
+impl TryFrom<u8> for Channel {
+    type Error = ChannelError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
+            0 => Ok(Channel::Channel1),
+            1 => Ok(Channel::Channel2),
+            2 => Ok(Channel::Channel3),
+            _ => Err(ChannelError::OutOfRange),
+        }
+    }
+}
+
+impl Channel {
+    /// The number of channels supported by the receivers.
+    pub const COUNT: usize = 3;
+
+    /// All channels, in ascending order.
+    pub const ALL: [Channel; Channel::COUNT] =
+        [Channel::Channel1, Channel::Channel2, Channel::Channel3];
+
+    /// The channel with the number 1–3 printed on the original remote, for
+    /// interfaces meant for people. Indices, e.g. for `Channel::try_from`,
+    /// start at 0 instead.
+    pub fn from_number(number: u8) -> Result<Self, ChannelError> {
+        let index = number.checked_sub(1).ok_or(ChannelError::OutOfRange)?;
+        Channel::try_from(index)
+    }
+
+    /// The signed distance from `other` to this channel, so that
+    /// `other + offset` is this channel for positive offsets and
+    /// `other - offset.unsigned_abs()` for negative ones.
+    pub fn offset_from(self, other: Channel) -> i8 {
+        self as i8 - other as i8
+    }
+}
+
+/// Adds an offset to the channel, wrapping around after the last one.
+///
+/// Unlike integer addition this never overflows: the result is taken modulo
+/// the number of channels, so it wraps in debug and release builds alike,
+/// e.g. `Channel::Channel3 + 1 == Channel::Channel1`.
+impl core::ops::Add<u8> for Channel {
+    type Output = Channel;
+
+    fn add(self, offset: u8) -> Channel {
+        let count = Channel::COUNT as u16;
+        match (self as u16 + u16::from(offset)) % count {
             0 => Channel::Channel1,
             1 => Channel::Channel2,
-            2 => Channel::Channel3,
-            _ => panic!("Invalid channel value"),
+            _ => Channel::Channel3,
         }
     }
 }
 
+/// Subtracts an offset from the channel, wrapping around before the first
+/// one.
+///
+/// Like [Add](core::ops::Add), this wraps in debug and release builds alike,
+/// e.g. `Channel::Channel1 - 1 == Channel::Channel3`.
+impl core::ops::Sub<u8> for Channel {
+    type Output = Channel;
+
+    fn sub(self, offset: u8) -> Channel {
+        let count = Channel::COUNT as u8;
+        self + (count - offset % count)
+    }
+}
+
+/// The commands understood by the receiver.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum Command {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Command {
     Shock = 1,
     Vibrate = 2,
     Beep = 3,
 }
 
-pub struct ChannelTransmitter<'a, 'b, PIN, DELAY, NOW>
-where
+/// The reason a value is not a valid [Command].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CommandError {
+    /// The value is not 1, 2 or 3.
+    OutOfRange,
+}
+
+/// Converts the value of a command as sent in a packet, 1–3, e.g. from a
+/// frame received over a serial link.
+impl TryFrom<u8> for Command {
+    type Error = CommandError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Command::Shock),
+            2 => Ok(Command::Vibrate),
+            3 => Ok(Command::Beep),
+            _ => Err(CommandError::OutOfRange),
+        }
+    }
+}
+
+/// A single command addressed to a receiver.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandPacket {
+    /// The ID of the transmitter the receiver is paired with.
+    pub id: u16,
+
+    /// The channel of the receiver.
+    pub channel: Channel,
+
+    /// The command to execute.
+    pub command: Command,
+
+    /// The strength of the command. Ignored by the receiver for beeps.
+    pub strength: u8,
+}
+
+impl CommandPacket {
+    /// Decodes the 42 bits of a packet as sent over the air, MSB first.
+    /// Returns `None` if a field is out of range or the checksum is wrong.
+    pub(crate) fn from_bits(bits: u64) -> Option<Self> {
+        let (packet, checksum_valid) = Self::from_bits_unchecked(bits)?;
+        checksum_valid.then_some(packet)
+    }
+
+    /// Like [from_bits](Self::from_bits), returning the packet together
+    /// with whether its checksum is correct instead of rejecting it.
+    pub(crate) fn from_bits_unchecked(bits: u64) -> Option<(Self, bool)> {
+        let channel = Channel::try_from(((bits >> 22) & 0xf) as u8).ok()?;
+        let command = Command::try_from(((bits >> 18) & 0xf) as u8).ok()?;
+        let packet = CommandPacket {
+            id: (bits >> 26) as u16,
+            channel,
+            command,
+            strength: (bits >> 10) as u8,
+        };
+
+        Some((packet, packet.checksum() == (bits >> 2) as u8))
+    }
+
+    /// The 42 bits of the packet as sent over the air, MSB first, the
+    /// inverse of [from_bits](Self::from_bits).
+    pub(crate) fn to_bits(self) -> u64 {
+        u64::from(self.id) << 26
+            | u64::from(self.channel as u8) << 22
+            | u64::from(self.command as u8) << 18
+            | u64::from(self.strength) << 10
+            | u64::from(self.checksum()) << 2
+    }
+
+    /// Writes the timings a Transmitter with the default settings sends for
+    /// this packet into `timings` and returns their number. Entries
+    /// alternate between low and high, starting low, in [Timing] units.
+    pub fn encode(&self, timings: &mut [Timing; MAX_TIMINGS]) -> usize {
+        encode(
+            self,
+            &PREAMBLE,
+            &FOOTER,
+            None,
+            Fec::None,
+            &PwmEncoder,
+            &ProtocolTiming::ch8803_default(),
+            timings,
+        )
+    }
+
+    /// The time a Transmitter with the default settings takes to send one
+    /// repetition of this packet, including the preamble and footer, in
+    /// microseconds. The [inter-packet gap](Transmitter::set_inter_packet_gap)
+    /// after every repetition is not included, so use
+    /// [packet_period_us](Transmitter::packet_period_us) to estimate the
+    /// number of repetitions sent for a duration.
+    pub fn total_duration_us(&self) -> u32 {
+        let mut timings = [0; MAX_TIMINGS];
+        let len = self.encode(&mut timings);
+        let ticks: u64 = timings[..len].iter().map(|&t| u64::from(t)).sum();
+        (ticks / TIMING_PER_US) as u32
+    }
+
+    /// The most and least significant byte of the ID, in the order they
+    /// are sent.
+    pub fn id_bytes(&self) -> (u8, u8) {
+        id_bytes(self.id)
+    }
+
+    fn checksum(&self) -> u8 {
+        encode_checksum(self.id, self.channel, self.command, self.strength)
+    }
+}
+
+/// A command with its duration as plain data, e.g. received as JSON from
+/// a PC over a serial port. Send it with [execute](Transmitter::execute).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedCommand {
+    pub channel: Channel,
+    pub command: Command,
+    /// The strength of the command. Ignored by the receiver for beeps.
+    pub strength: u8,
+    pub duration_ms: u32,
+}
+
+/// An error that occurred while sending a packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TransmitError {
+    /// Setting the level of the data pin failed at least once.
+    Pin,
+}
+
+/// The reason [send_bytes](Transmitter::send_bytes) did not send a payload.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PayloadError {
+    /// The encoded payload does not fit into [MAX_TIMINGS] together with
+    /// the preamble and footer.
+    TooLong,
+    /// A [max_strength](Transmitter::set_max_strength) is set, which cannot
+    /// be applied to a raw payload.
+    StrengthLimited,
+}
+
+/// The reason a confirmed command like
+/// [shock_safe](ChannelTransmitter::shock_safe) was not sent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AckError {
+    /// The confirmed strength differs from the requested one.
+    StrengthMismatch,
+}
+
+/// The details of a command sent by
+/// [send_command_recorded](Transmitter::send_command_recorded).
+#[cfg(feature = "diagnostics")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CommandRecord {
+    /// The packet that was sent.
+    pub packet: CommandPacket,
+    /// The time the first repetition started.
+    pub sent_at: Instant,
+    /// How often the packet was repeated.
+    pub repetitions: u32,
+    /// The time the transmission actually took, which is slightly longer
+    /// than requested because the last repetition is always completed.
+    pub actual_duration: Duration,
+    /// The first error that occurred, if any. The transmission continues
+    /// after errors.
+    pub error: Option<TransmitError>,
+}
+
+/// The result of [verify_timing](Transmitter::verify_timing).
+#[cfg(feature = "diagnostics")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimingReport {
+    /// The sum of all pulse lengths of one repetition.
+    pub expected_us: u64,
+    /// The time the delays of one repetition actually took.
+    pub measured_us: u64,
+    /// How much longer the measured time is, in percent of the expected one.
+    pub deviation_pct: i8,
+}
+
+/// The outcome of [send_batch](Transmitter::send_batch) and
+/// [send_batch_with_gaps](Transmitter::send_batch_with_gaps).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchResult {
+    /// The number of packets sent without errors.
+    pub sent: usize,
+    /// The index of the first packet whose transmission failed.
+    pub first_error: Option<usize>,
+}
+
+/// The timings of the last [BATCH_CACHE] distinct packets of a batch, so
+/// that packets repeated anywhere within that window are encoded only once.
+/// Once full, the packet encoded first is replaced.
+struct BatchCache {
+    packets: [Option<CommandPacket>; BATCH_CACHE],
+    timings: [[Timing; MAX_TIMINGS]; BATCH_CACHE],
+    lens: [usize; BATCH_CACHE],
+    /// The slot replaced by the next packet that is not cached.
+    next: usize,
+}
+
+impl BatchCache {
+    fn new() -> Self {
+        Self {
+            packets: [None; BATCH_CACHE],
+            timings: [[0; MAX_TIMINGS]; BATCH_CACHE],
+            lens: [0; BATCH_CACHE],
+            next: 0,
+        }
+    }
+
+    /// Returns the timings of `packet`, calling `encode` to write them and
+    /// return their count if the packet is not cached.
+    fn get_or_encode(
+        &mut self,
+        packet: &CommandPacket,
+        encode: impl FnOnce(&mut [Timing; MAX_TIMINGS]) -> usize,
+    ) -> &[Timing] {
+        let slot = match self.packets.iter().position(|p| p == &Some(*packet)) {
+            Some(slot) => slot,
+            None => {
+                let slot = self.next;
+                self.next = (slot + 1) % BATCH_CACHE;
+                self.lens[slot] = encode(&mut self.timings[slot]);
+                self.packets[slot] = Some(*packet);
+                slot
+            }
+        };
+        &self.timings[slot][..self.lens[slot]]
+    }
+}
+
+/// The timings of one repetition together with the packet they encode, if
+/// any.
+type Frame<'t> = (&'t [Timing], Option<&'t CommandPacket>);
+
+/// The outcome of repeating a packet.
+pub(crate) struct Transmission {
+    pub(crate) repetitions: u32,
+    /// The repetitions that were sent without errors.
+    pub(crate) sent: u32,
+    pub(crate) error: Option<TransmitError>,
+}
+
+/// Up to `N` pulse lengths in microseconds, alternating between the low and
+/// the high level and starting low, e.g. a custom preamble. Unused entries
+/// are zero.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimingSequence<const N: usize> {
+    timings: [u16; N],
+    len: u8,
+}
+
+impl<const N: usize> TimingSequence<N> {
+    /// Copies `timings` into a new sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timings` has more than `N` entries.
+    pub const fn new(timings: &[u16]) -> Self {
+        assert!(timings.len() <= N, "too many timings for the sequence");
+        let mut sequence = [0; N];
+        let mut i = 0;
+        while i < timings.len() {
+            sequence[i] = timings[i];
+            i += 1;
+        }
+        Self {
+            timings: sequence,
+            len: timings.len() as u8,
+        }
+    }
+
+    /// The valid entries of the sequence.
+    pub fn as_slice(&self) -> &[u16] {
+        &self.timings[..usize::from(self.len)]
+    }
+}
+
+/// The pulse lengths of a protocol variant in microseconds, e.g. of a CH8803
+/// clone with a slightly different clock. Set them with
+/// [set_protocol_timing](Transmitter::set_protocol_timing).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProtocolTiming {
+    /// The length of a bit, high and low pulse together.
+    pub pulse_len: u16,
+
+    /// The length of the high pulse of a `0` bit. A length above
+    /// `pulse_len` leaves no low pulse after it.
+    pub zero_len: u16,
+
+    /// The length of the high pulse of a `1` bit. A length above
+    /// `pulse_len` leaves no low pulse after it.
+    pub one_len: u16,
+
+    /// The long high pulse of the preamble.
+    pub preamble_high: u16,
+
+    /// The low pulse before the high pulse of the preamble.
+    pub preamble_low: u16,
+
+    /// The low pulse after a packet, before the next repetition.
+    pub gap_len: u16,
+
+    /// The frequency in Hz the pin is toggled with during high pulses, for
+    /// ASK modules that suppress a constant high level. Zero holds the pin
+    /// high instead.
+    pub carrier_hz: u32,
+
+    /// The share of every carrier period the pin is high, in percent.
+    pub carrier_duty_pct: u8,
+}
+
+impl ProtocolTiming {
+    /// The timings of the original CH8803 remote.
+    pub const fn ch8803_default() -> Self {
+        Self {
+            pulse_len: 1016,
+            zero_len: 292,
+            one_len: 804,
+            preamble_high: PREAMBLE[1],
+            preamble_low: PREAMBLE[0],
+            gap_len: FOOTER[1],
+            carrier_hz: 0,
+            carrier_duty_pct: 50,
+        }
+    }
+
+    /// The preamble built from these timings, ending with the gap before
+    /// the high pulse of the first bit.
+    pub const fn preamble(&self) -> TimingSequence<MAX_PREAMBLE> {
+        TimingSequence::new(&[
+            self.preamble_low,
+            self.preamble_high,
+            self.pulse_len.saturating_sub(self.zero_len),
+        ])
+    }
+
+    /// The footer built from these timings: a final short pulse and the
+    /// gap before the next packet.
+    pub const fn footer(&self) -> TimingSequence<MAX_FOOTER> {
+        TimingSequence::new(&[self.zero_len, self.gap_len])
+    }
+}
+
+impl Default for ProtocolTiming {
+    fn default() -> Self {
+        Self::ch8803_default()
+    }
+}
+
+/// The timings of a complete packet as sent by a [Transmitter], returned by
+/// [encode_packet](Transmitter::encode_packet). Entries alternate between
+/// low and high, starting low, in [Timing] units; zeros keep the level.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacketTimings {
+    timings: [Timing; MAX_TIMINGS],
+    len: usize,
+}
+
+impl PacketTimings {
+    /// The valid entries.
+    pub fn as_slice(&self) -> &[Timing] {
+        &self.timings[..self.len]
+    }
+}
+
+impl AsRef<[Timing]> for PacketTimings {
+    fn as_ref(&self) -> &[Timing] {
+        self.as_slice()
+    }
+}
+
+/// Formats the valid entries only.
+impl fmt::Debug for PacketTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+/// The configuration of a [Transmitter], as captured by
+/// [checkpoint](Transmitter::checkpoint). Hardware state like the pin level is
+/// not included.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TransmitterSnapshot {
+    /// The ID of the device.
+    pub id: u16,
+
+    /// The whitening applied to the payload, if any.
+    pub whitening: Option<DataWhitening>,
+
+    /// The error correction applied to the payload.
+    pub fec: Fec,
+
+    /// The maximum random delay before every repetition, in microseconds.
+    pub tx_jitter_us: u16,
+
+    /// The additional time every level is held, in microseconds.
+    pub inter_bit_gap_us: u16,
+
+    /// The minimum number of repetitions of every packet.
+    pub min_repeats: u8,
+
+    /// The maximum number of repetitions of every packet, if limited.
+    pub max_repeats: Option<u8>,
+
+    /// The silence after every repetition of a packet.
+    pub inter_packet_gap: Duration,
+
+    /// The preamble sent before every packet.
+    pub preamble: TimingSequence<MAX_PREAMBLE>,
+
+    /// The footer sent after every packet.
+    pub footer: TimingSequence<MAX_FOOTER>,
+
+    /// The pulse lengths of the bits.
+    pub protocol_timing: ProtocolTiming,
+
+    /// The strength every command is limited to, if any.
+    pub max_strength: Option<u8>,
+}
+
+/// Implements `shock`, `vibrate` and `beep` and their `_ms` variants for a
+/// transmitter bound to a channel, on top of its `send` method taking the
+/// command, the strength and the duration. `mut` takes `&mut self`, `async`
+/// makes the methods async and awaits `send`.
+macro_rules! channel_commands {
+    (async) => {
+        /// Sends a shock command to the receiver.
+        pub async fn shock(
+            &mut self,
+            strength: u8,
+            duration: $crate::ch8803::Duration,
+        ) {
+            self.send($crate::ch8803::Command::Shock, strength, duration)
+                .await;
+        }
+
+        /// Wrapper for the [shock](Self::shock) method that takes duration in milliseconds.
+        pub async fn shock_ms(&mut self, strength: u8, duration: u32) {
+            self.shock(strength, $crate::ch8803::Duration::millis(duration))
+                .await;
+        }
+
+        /// Sends a vibration command to the receiver.
+        pub async fn vibrate(
+            &mut self,
+            strength: u8,
+            duration: $crate::ch8803::Duration,
+        ) {
+            self.send($crate::ch8803::Command::Vibrate, strength, duration)
+                .await;
+        }
+
+        /// Wrapper for the [vibrate](Self::vibrate) method that takes duration in milliseconds.
+        pub async fn vibrate_ms(&mut self, strength: u8, duration: u32) {
+            self.vibrate(strength, $crate::ch8803::Duration::millis(duration))
+                .await;
+        }
+
+        /// Sends a beep command to the receiver.
+        pub async fn beep(&mut self, duration: $crate::ch8803::Duration) {
+            self.send($crate::ch8803::Command::Beep, 0, duration).await;
+        }
+
+        /// Wrapper for the [beep](Self::beep) method that takes duration in milliseconds.
+        pub async fn beep_ms(&mut self, duration: u32) {
+            self.beep($crate::ch8803::Duration::millis(duration)).await;
+        }
+    };
+    ($($mut:tt)?) => {
+        /// Sends a shock command to the receiver.
+        pub fn shock(
+            &$($mut)? self,
+            strength: u8,
+            duration: $crate::ch8803::Duration,
+        ) {
+            self.send($crate::ch8803::Command::Shock, strength, duration);
+        }
+
+        /// Wrapper for the [shock](Self::shock) method that takes duration in milliseconds.
+        pub fn shock_ms(&$($mut)? self, strength: u8, duration: u32) {
+            self.shock(strength, $crate::ch8803::Duration::millis(duration));
+        }
+
+        /// Sends a vibration command to the receiver.
+        pub fn vibrate(
+            &$($mut)? self,
+            strength: u8,
+            duration: $crate::ch8803::Duration,
+        ) {
+            self.send($crate::ch8803::Command::Vibrate, strength, duration);
+        }
+
+        /// Wrapper for the [vibrate](Self::vibrate) method that takes duration in milliseconds.
+        pub fn vibrate_ms(&$($mut)? self, strength: u8, duration: u32) {
+            self.vibrate(strength, $crate::ch8803::Duration::millis(duration));
+        }
+
+        /// Sends a beep command to the receiver.
+        pub fn beep(&$($mut)? self, duration: $crate::ch8803::Duration) {
+            self.send($crate::ch8803::Command::Beep, 0, duration);
+        }
+
+        /// Wrapper for the [beep](Self::beep) method that takes duration in milliseconds.
+        pub fn beep_ms(&$($mut)? self, duration: u32) {
+            self.beep($crate::ch8803::Duration::millis(duration));
+        }
+    };
+}
+pub(crate) use channel_commands;
+
+pub struct ChannelTransmitter<
+    'a,
+    'b,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+    CH = AnyChannel,
+> where
     PIN: OutputPin,
-    DELAY: DelayUs<u16>,
+    DELAY: PulseDelay,
     NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
 {
-    device: &'b mut Transmitter<'a, PIN, DELAY, NOW>,
+    device: &'b mut Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
     channel: Channel,
+    _channel: PhantomData<CH>,
 }
 
-impl<'a, 'b, PIN, DELAY, NOW> ChannelTransmitter<'a, 'b, PIN, DELAY, NOW>
+impl<'a, 'b, PIN, DELAY, NOW, ENC, LED, BUSY, CH>
+    ChannelTransmitter<'a, 'b, PIN, DELAY, NOW, ENC, LED, BUSY, CH>
 where
     PIN: OutputPin,
-    DELAY: DelayUs<u16>,
+    DELAY: PulseDelay,
     NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
 {
-    /// Sends a shock command to the receiver.
-    pub fn shock(&mut self, strength: u8, duration: Duration) {
-        self.device.send_command(
+    channel_commands!(mut);
+
+    /// The Transmitter this is bound to, e.g. to change its settings
+    /// between commands.
+    pub fn transmitter(
+        &mut self,
+    ) -> &mut Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        self.device
+    }
+
+    /// Releases the binding to the channel and returns the Transmitter.
+    pub fn into_transmitter(
+        self,
+    ) -> &'b mut Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        self.device
+    }
+
+    /// Compensates a systematic deviation of the timer, e.g. after a
+    /// command sent `actual_packet_count` repetitions where
+    /// `expected_packet_count` were expected for its duration. The durations
+    /// of everything the Transmitter sends for a duration afterwards, e.g.
+    /// [send_command](Transmitter::send_command),
+    /// [send_packet](Transmitter::send_packet) or
+    /// [send_hopping](Transmitter::send_hopping), are divided by the ratio of
+    /// the two; commands with a fixed number of repetitions like
+    /// [send_command_n](Transmitter::send_command_n) are not affected.
+    /// Calibrating again refines the stored ratio, which is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either count is zero.
+    pub fn calibrate(
+        &mut self,
+        expected_packet_count: u32,
+        actual_packet_count: u32,
+    ) -> f32 {
+        assert!(
+            expected_packet_count > 0 && actual_packet_count > 0,
+            "packet counts must not be zero"
+        );
+        let ratio = actual_packet_count as f32 / expected_packet_count as f32;
+        self.device.timing_correction *= ratio;
+        self.device.timing_correction
+    }
+
+    /// Sends a shock command with exactly `repetitions` packets, see
+    /// [send_command_n](Transmitter::send_command_n).
+    pub fn shock_n(&mut self, strength: u8, repetitions: u16) {
+        let Ok(_) = self.device.send_command_n(
             self.channel,
             Command::Shock,
             strength,
-            duration,
+            repetitions,
         );
     }
 
-    /// Wrapper for the [shock](Self::shock) method that takes duration in milliseconds.
-    pub fn shock_ms(&mut self, strength: u8, duration: u32) {
-        self.shock(strength, Duration::millis(duration));
+    /// Sends a shock command only if `ack_strength` matches `strength`, e.g.
+    /// when an operator has to enter the displayed level again to confirm
+    /// it. Nothing is sent on a mismatch.
+    pub fn shock_safe(
+        &mut self,
+        strength: u8,
+        duration: impl Into<Duration>,
+        ack_strength: u8,
+    ) -> Result<(), AckError> {
+        if strength != ack_strength {
+            return Err(AckError::StrengthMismatch);
+        }
+
+        self.shock(strength, duration.into());
+        Ok(())
+    }
+
+    /// Shocks with a strength that rises linearly from 1 to `peak` and falls
+    /// back to 1 within each `period_ms`, using 16 steps per period. Only
+    /// full periods that fit into `total_ms` are sent.
+    ///
+    /// Like for [vibrate_sine_steps](Self::vibrate_sine_steps), fewer steps
+    /// are used if they would be shorter than one packet period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period_ms` is zero.
+    pub fn shock_triangle(&mut self, peak: u8, period_ms: u32, total_ms: u32) {
+        let (steps, step) = self.waveform_steps(period_ms, TRIANGLE_STEPS);
+        let range = u32::from(peak.saturating_sub(1));
+
+        for i in 0..total_ms / period_ms * steps {
+            let phase = ((i % steps) * 256 / steps) as u8;
+            let level = u32::from(waveform::triangle_u8(phase));
+            self.shock((1 + range * level / 255) as u8, step);
+        }
     }
 
-    /// Sends a vibration command to the receiver.
-    pub fn vibrate(&mut self, strength: u8, duration: Duration) {
-        self.device.send_command(
+    /// Sends a vibration command with exactly `repetitions` packets, see
+    /// [send_command_n](Transmitter::send_command_n).
+    pub fn vibrate_n(&mut self, strength: u8, repetitions: u16) {
+        let Ok(_) = self.device.send_command_n(
             self.channel,
             Command::Vibrate,
             strength,
-            duration,
+            repetitions,
         );
     }
 
-    /// Wrapper for the [vibrate](Self::vibrate) method that takes duration in milliseconds.
-    pub fn vibrate_ms(&mut self, strength: u8, duration: u32) {
-        self.vibrate(strength, Duration::millis(duration));
+    /// Vibrates with a strength following a sine wave between 0 and
+    /// `peak_strength`, using 16 steps per period. Only full periods that fit
+    /// into `total_ms` are sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period_ms` is zero.
+    pub fn vibrate_sine(
+        &mut self,
+        peak_strength: u8,
+        period_ms: u32,
+        total_ms: u32,
+    ) {
+        self.vibrate_sine_steps(peak_strength, period_ms, total_ms, 16);
     }
 
-    /// Sends a beep command to the receiver.
-    pub fn beep(&mut self, duration: Duration) {
-        self.device
-            .send_command(self.channel, Command::Beep, 0, duration);
-    }
+    /// Variant of [vibrate_sine](Self::vibrate_sine) with a custom number of
+    /// steps per period.
+    ///
+    /// Every step sends at least one packet, so steps shorter than the
+    /// [packet_period_us](Transmitter::packet_period_us) would stretch the
+    /// waveform. Their number is reduced until every step lasts at least
+    /// that long, down to a single step per period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period_ms` or `steps` is zero.
+    pub fn vibrate_sine_steps(
+        &mut self,
+        peak_strength: u8,
+        period_ms: u32,
+        total_ms: u32,
+        steps: u8,
+    ) {
+        assert!(steps > 0, "the number of steps must not be zero");
+        let (steps, step) = self.waveform_steps(period_ms, u32::from(steps));
 
-    /// Wrapper for the [beep](Self::beep) method that takes duration in milliseconds.
-    pub fn beep_ms(&mut self, duration: u32) {
-        self.beep(Duration::millis(duration));
+        for i in 0..total_ms / period_ms * steps {
+            // Shift by a quarter turn so that every period starts at zero.
+            let phase = ((i % steps) * 256 / steps) as u8;
+            let level = waveform::sin_u8(phase.wrapping_add(192));
+            let strength = u16::from(peak_strength) * u16::from(level) / 255;
+            self.vibrate(strength as u8, step);
+        }
     }
-}
 
-#[derive(TypedBuilder)]
-pub struct Transmitter<'a, PIN, DELAY, NOW>
-where
-    PIN: OutputPin,
-    DELAY: DelayUs<u16>,
-    NOW: InstantFn,
-{
-    /// The pin used to transmit the signal. This pin should be connected to the DATA pin
-    /// of a 433 MHz transmitter module.
-    pin: PIN,
+    /// Vibrates with a strength that ramps linearly from 0 up to
+    /// `peak_strength` and back down within each `breath_period_ms`, until
+    /// `total_ms` have passed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `breath_period_ms` is zero.
+    pub fn vibrate_breathe(
+        &mut self,
+        peak_strength: u8,
+        breath_period_ms: u32,
+        total_ms: u32,
+    ) {
+        let period = u64::from(breath_period_ms);
+        let step_ms = (breath_period_ms / BREATHE_STEPS).max(1);
+        let start = (self.device.now_fn)();
 
-    /// The delay implementation used to control the timing of the signal.
-    delay: &'a RefCell<DELAY>,
+        loop {
+            let elapsed = ((self.device.now_fn)() - start).to_millis();
+            if elapsed >= u64::from(total_ms) {
+                break;
+            }
 
-    /// A function that returns the current ticks.
-    now_fn: NOW,
+            let phase = ((elapsed % period) * 256 / period) as u8;
+            let level = waveform::triangle_u8(phase);
+            let strength = u16::from(peak_strength) * u16::from(level) / 255;
+            let remaining = (u64::from(total_ms) - elapsed) as u32;
+            self.vibrate_ms(strength as u8, step_ms.min(remaining));
+        }
+    }
 
-    /// The ID of the device. This should be a unique identifier for the transmitter
-    /// and is used together with the channel to pair a receiver.
-    id: u16,
-}
+    /// Vibrates with a strength that changes linearly from `start` to `end`
+    /// over `total_duration`, in steps of `step_duration`, e.g. to fade in
+    /// instead of starting at the full strength. See
+    /// [ramp_shock](Self::ramp_shock) for the details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step_duration` is zero.
+    pub fn ramp_vibrate(
+        &mut self,
+        start: u8,
+        end: u8,
+        total_duration: Duration,
+        step_duration: Duration,
+    ) {
+        self.ramp(Command::Vibrate, start, end, total_duration, step_duration);
+    }
 
-impl<'a, PIN, DELAY, NOW> Transmitter<'a, PIN, DELAY, NOW>
-where
-    PIN: OutputPin,
-    DELAY: DelayUs<u16>,
-    NOW: InstantFn,
-{
-    /// Binds the Transmitter to a specific channel, allowing to send actual commands.
-    pub fn channel<'b>(
-        &'b mut self,
-        channel: impl Into<Channel>,
-    ) -> ChannelTransmitter<'a, 'b, PIN, DELAY, NOW> {
-        ChannelTransmitter {
-            device: self,
-            channel: channel.into(),
-        }
+    /// Shocks with a strength that changes linearly from `start` to `end`
+    /// over `total_duration`, in steps of `step_duration`. The first step
+    /// uses `start` and the last one `end`, and the last step also takes the
+    /// time left over by the division. A `step_duration` longer than
+    /// `total_duration` sends a single step at `start`. The
+    /// [max_strength](Transmitter::set_max_strength) applies to every step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step_duration` is zero.
+    pub fn ramp_shock(
+        &mut self,
+        start: u8,
+        end: u8,
+        total_duration: Duration,
+        step_duration: Duration,
+    ) {
+        self.ramp(Command::Shock, start, end, total_duration, step_duration);
     }
 
-    fn send_command(
+    fn ramp(
         &mut self,
-        channel: Channel,
         command: Command,
-        strength: u8,
-        duration: Duration,
+        start: u8,
+        end: u8,
+        total_duration: Duration,
+        step_duration: Duration,
     ) {
-        let checksum = ((self.id >> 8) as u8)
-            .wrapping_add(self.id as u8)
-            .wrapping_add(channel as u8)
-            .wrapping_add(command as u8)
-            .wrapping_add(strength);
+        let total = total_duration.to_micros();
+        let step = step_duration.to_micros();
+        assert!(step > 0, "the step duration must not be zero");
+        let steps = (total / step).max(1);
+
+        let range = i64::from(end) - i64::from(start);
+        for i in 0..steps {
+            let strength = if steps == 1 {
+                start
+            } else {
+                let offset = range * i64::from(i) / i64::from(steps - 1);
+                (i64::from(start) + offset) as u8
+            };
+            let micros = if i + 1 == steps {
+                total - step * i
+            } else {
+                step
+            };
+            self.send(command, strength, Duration::micros(micros));
+        }
+    }
 
-        let mut timings = TimingVec::new();
+    /// Shocks starting at the `start` strength of `policy` and raises it by
+    /// `step` after every `step_interval_ms`, up to `max`, until `total_ms`
+    /// have passed. `confirm` is called before every step and ends the shock
+    /// early when it returns `true`, e.g. once an observer saw a reaction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step_interval_ms` is zero.
+    pub fn adaptive_shock(
+        &mut self,
+        policy: AdaptiveStrengthPolicy,
+        confirm: impl Fn() -> bool,
+        total_ms: u32,
+    ) {
+        let interval = u64::from(policy.step_interval_ms);
+        let start = (self.device.now_fn)();
 
-        timings.extend([840, 1440, PULSE_LEN - ZERO_LEN]);
-        Self::trbits(self.id, 16, &mut timings);
-        Self::trbits(channel as u8, 4, &mut timings);
-        Self::trbits(command as u8, 4, &mut timings);
-        Self::trbits(strength, 8, &mut timings);
-        Self::trbits(checksum, 8, &mut timings);
-        Self::trbits(0u16, 2, &mut timings);
-        timings.extend([ZERO_LEN, 1476]);
+        loop {
+            let elapsed = ((self.device.now_fn)() - start).to_millis();
+            if elapsed >= u64::from(total_ms) || confirm() {
+                break;
+            }
 
-        let end = (self.now_fn)() + duration;
-        while (self.now_fn)() < end {
-            self.send_timing(&timings);
+            let step = elapsed / interval;
+            let strength =
+                policy.strength_at(step.min(u64::from(u32::MAX)) as u32);
+            let step_end = (step + 1) * interval;
+            let remaining = step_end.min(u64::from(total_ms)) - elapsed;
+            self.shock_ms(strength, remaining as u32);
         }
     }
 
-    fn send_timing(&mut self, timings: &[u16]) {
-        let mut level = false;
-        for &duration in timings.iter() {
-            let _ = if level {
-                self.pin.set_high()
-            } else {
-                self.pin.set_low()
+    /// Repeatedly samples an ADC (e.g. connected to a potentiometer) and sends
+    /// `command` for `duration_per_sample_ms` with the reading mapped to a
+    /// strength of 1–99. The full range of a 12-bit converter is assumed.
+    ///
+    /// This only returns if reading the ADC fails.
+    #[cfg(feature = "adc")]
+    pub fn follow_analog<ADC, A, P>(
+        &mut self,
+        adc: &mut A,
+        pin: &mut P,
+        command: Command,
+        duration_per_sample_ms: u32,
+    ) -> Result<Infallible, A::Error>
+    where
+        A: OneShot<ADC, u16, P>,
+        P: adc::Channel<ADC>,
+    {
+        loop {
+            let reading = match adc.read(pin) {
+                Ok(reading) => reading.min(ADC_MAX),
+                Err(nb::Error::WouldBlock) => continue,
+                Err(nb::Error::Other(err)) => return Err(err),
             };
-            self.delay.borrow_mut().delay_us(duration);
-            level = !level;
+            let strength = 1 + u32::from(reading) * 98 / u32::from(ADC_MAX);
+
+            self.send(
+                command,
+                strength as u8,
+                Duration::millis(duration_per_sample_ms),
+            );
+        }
+    }
+
+    /// Sends a beep command with exactly `repetitions` packets, see
+    /// [send_command_n](Transmitter::send_command_n).
+    pub fn beep_n(&mut self, repetitions: u16) {
+        let Ok(_) = self.device.send_command_n(
+            self.channel,
+            Command::Beep,
+            0,
+            repetitions,
+        );
+    }
+
+    /// Beeps following a pattern of `(on_ms, off_ms)` pairs, e.g. for Morse
+    /// code. Every beep of `on_ms` is followed by `off_ms` of silence, so
+    /// `&[(100, 100), (100, 100), (500, 0)]` beeps "dit-dit-dah".
+    pub fn beep_pattern(&mut self, pattern: &[(u32, u32)]) {
+        for &(on_ms, off_ms) in pattern {
+            self.beep_ms(on_ms);
+            self.device.wait_micros(off_ms.saturating_mul(1000));
         }
-        let _ = self.pin.set_low();
     }
 
-    fn trbits(val: impl Into<u16>, bits: u8, timings: &mut TimingVec) {
-        let val = val.into();
+    /// Sends a raw timing sequence wrapped in the standard preamble and footer
+    /// for the given duration. The timings alternate between high and low,
+    /// starting with high, just like the bits of a regular command.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timings` has more than 288 entries.
+    pub fn tone(&mut self, timings: &[Timing], duration: impl Into<Duration>) {
+        let mut packet = [0; MAX_TIMINGS];
+        let mut idx = 0;
+
+        push_us(&mut packet, &mut idx, self.device.preamble.as_slice());
+        push(&mut packet, &mut idx, timings);
+        push_us(&mut packet, &mut idx, self.device.footer.as_slice());
+
+        self.device.repeat(
+            &packet[..idx],
+            duration.into(),
+            None,
+            &no_inspection,
+        );
+    }
+
+    /// Reduces `steps` per period of `period_ms` so that every step lasts
+    /// at least one packet period, keeping at least one step. Returns the
+    /// number of steps and their duration.
+    fn waveform_steps(&self, period_ms: u32, steps: u32) -> (u32, Duration) {
+        let period_us = u64::from(period_ms) * 1000;
+        let packet_us = u64::from(self.device.packet_period_us()).max(1);
+        let steps = (period_us / packet_us).clamp(1, u64::from(steps));
+        let step_us = (period_us / steps).min(u64::from(u32::MAX));
+        (steps as u32, Duration::micros(step_us as u32))
+    }
+
+    fn send(&mut self, command: Command, strength: u8, duration: Duration) {
+        let Ok(_) =
+            self.device
+                .send_command(self.channel, command, strength, duration);
+    }
+}
+
+/// A [ChannelTransmitter] that only borrows the Transmitter while a command
+/// is sent, created with [channel_shared](Transmitter::channel_shared).
+///
+/// Several of them can be bound to different channels of the same
+/// Transmitter at once, e.g. for an event loop dispatching commands to the
+/// channels alternately. In exchange, borrowing is checked at runtime: every
+/// command panics if the Transmitter is already borrowed, e.g. by a command
+/// of another SharedChannelTransmitter sent from a packet observer.
+pub struct SharedChannelTransmitter<
+    'a,
+    'b,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    device: &'b RefCell<Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>>,
+    channel: Channel,
+}
+
+impl<PIN, DELAY, NOW, ENC, LED, BUSY>
+    SharedChannelTransmitter<'_, '_, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    channel_commands!();
+
+    /// The channel this is bound to.
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    fn send(&self, command: Command, strength: u8, duration: Duration) {
+        let Ok(_) = self.device.borrow_mut().send_command(
+            self.channel,
+            command,
+            strength,
+            duration,
+        );
+    }
+}
+
+/// Formats the ID of the Transmitter, e.g. `0x0d25`.
+impl<PIN, DELAY, NOW, ENC, LED, BUSY> fmt::LowerHex
+    for Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x}", self.id)
+    }
+}
+
+/// Formats the ID of the Transmitter, e.g. `0x0D25`.
+impl<PIN, DELAY, NOW, ENC, LED, BUSY> fmt::UpperHex
+    for Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06X}", self.id)
+    }
+}
+
+/// Formats the Transmitter as e.g. `Transmitter(id=0x0d25)`.
+impl<PIN, DELAY, NOW, ENC, LED, BUSY> fmt::Display
+    for Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transmitter(id={:#06x})", self.id)
+    }
+}
+
+impl<PIN, DELAY, NOW, ENC, LED, BUSY, CH>
+    From<ChannelTransmitter<'_, '_, PIN, DELAY, NOW, ENC, LED, BUSY, CH>>
+    for Channel
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+    CH: FixedChannel,
+{
+    fn from(
+        _: ChannelTransmitter<'_, '_, PIN, DELAY, NOW, ENC, LED, BUSY, CH>,
+    ) -> Self {
+        CH::CHANNEL
+    }
+}
+
+/// The settings required to create a [Transmitter] with
+/// [from_config](Transmitter::from_config), e.g. when they are read at once
+/// from a config file.
+pub struct TransmitterConfig<'a, PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+{
+    /// The pin connected to the DATA pin of a 433 MHz transmitter module.
+    pub pin: PIN,
+
+    /// The delay implementation used to control the timing of the signal.
+    pub delay: &'a RefCell<DELAY>,
+
+    /// A function that returns the current ticks.
+    pub now_fn: NOW,
+
+    /// The ID of the device, used together with the channel to pair a
+    /// receiver.
+    pub id: u16,
+}
+
+/// Sends commands to CH8803 receivers.
+///
+/// The delay is borrowed as a shared `RefCell`, which is not `Sync`, so the
+/// Transmitter is not `Send` either. Create both the delay and the Transmitter
+/// in the thread or interrupt context that uses them.
+pub struct Transmitter<
+    'a,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// The pin used to transmit the signal. This pin should be connected to the DATA pin
+    /// of a 433 MHz transmitter module.
+    pin: PIN,
+
+    /// The delay implementation used to control the timing of the signal.
+    delay: &'a RefCell<DELAY>,
+
+    /// A function that returns the current ticks.
+    now_fn: NOW,
+
+    /// The ID of the device. This should be a unique identifier for the transmitter
+    /// and is used together with the channel to pair a receiver.
+    id: u16,
+
+    /// The whitening applied to the payload of command packets. Use
+    /// [set_whitening](Self::set_whitening) to enable it.
+    whitening: Option<DataWhitening>,
+
+    /// The error correction applied to the payload of command packets. Use
+    /// [set_fec](Self::set_fec) to change it.
+    fec: Fec,
+
+    /// The maximum random delay before every repetition of a packet, in
+    /// microseconds. Use [set_tx_jitter](Self::set_tx_jitter) to enable it.
+    tx_jitter_us: u16,
+
+    /// The time every level is held in addition to its timing, in
+    /// microseconds. Use [set_inter_bit_gap](Self::set_inter_bit_gap) to
+    /// enable it.
+    inter_bit_gap_us: u16,
+
+    /// The minimum number of repetitions of every packet, even if the
+    /// duration has passed. Use [set_min_repeats](Self::set_min_repeats) to
+    /// change it.
+    min_repeats: u8,
+
+    /// The maximum number of repetitions of every packet, even if the
+    /// duration has not passed yet. Use
+    /// [set_max_repeats](Self::set_max_repeats) to limit it.
+    max_repeats: Option<u8>,
+
+    /// The time the pin is held low after every repetition of a packet, in
+    /// addition to the gap at the end of the footer. Use
+    /// [set_inter_packet_gap](Self::set_inter_packet_gap) to change it.
+    inter_packet_gap: Duration,
+
+    /// The timings sent before the bits of every packet. Use
+    /// [set_preamble](Self::set_preamble) to replace them.
+    preamble: TimingSequence<MAX_PREAMBLE>,
+
+    /// The timings sent after the bits of every packet. Use
+    /// [set_footer](Self::set_footer) to replace them.
+    footer: TimingSequence<MAX_FOOTER>,
+
+    /// The strength every command is limited to. Use
+    /// [set_max_strength](Self::set_max_strength) to set it.
+    max_strength: Option<u8>,
+
+    /// The pulse lengths of the bits, used by encoders that support them.
+    /// Use [set_protocol_timing](Self::set_protocol_timing) to change them.
+    protocol_timing: ProtocolTiming,
+
+    /// The scheme used to encode the packet bits into pulse timings. Use
+    /// [with_encoder](Self::with_encoder) to replace it.
+    encoder: ENC,
+
+    /// The LED that is lit during transmissions. Use
+    /// [with_led](Self::with_led) to add it.
+    led: Option<LedIndicator<LED>>,
+
+    /// A pin that is high during every repetition of a packet. Use
+    /// [with_busy_pin](Self::with_busy_pin) to add it.
+    busy: Option<BUSY>,
+
+    /// The ratio of the packets actually sent to the expected ones, which
+    /// the durations of commands are divided by. Use
+    /// [calibrate](ChannelTransmitter::calibrate) to measure it.
+    timing_correction: f32,
+
+    #[cfg(feature = "observer")]
+    observer: Option<PacketObserver>,
+
+    /// The last packet sent for a duration, for
+    /// [verify_timing](Self::verify_timing).
+    #[cfg(feature = "diagnostics")]
+    last_packet: Option<CommandPacket>,
+}
+
+impl<'a, PIN, DELAY, NOW> Transmitter<'a, PIN, DELAY, NOW>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+{
+    /// Creates a builder for a Transmitter using the default [PwmEncoder].
+    /// The settings have to be given in order, starting with the pin.
+    pub fn builder() -> TransmitterBuilder<'a, NeedPin, PIN, DELAY, NOW> {
+        TransmitterBuilder::new()
+    }
+
+    /// Creates a Transmitter with the ID 1 and default settings, for quick
+    /// prototyping. Prefer the [builder](Self::builder) to choose a unique
+    /// ID.
+    pub fn new(pin: PIN, delay: &'a RefCell<DELAY>, now_fn: NOW) -> Self {
+        Self::from_config(TransmitterConfig {
+            pin,
+            delay,
+            now_fn,
+            id: 0x0001,
+        })
+    }
+
+    /// Creates a Transmitter with the protocol settings of `model`, as a
+    /// shortcut for setting them one by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the preamble of a [Custom](CollarModel::Custom) model has
+    /// an even number of entries, see [set_preamble](Self::set_preamble).
+    pub fn for_model(
+        model: CollarModel,
+        pin: PIN,
+        delay: &'a RefCell<DELAY>,
+        now_fn: NOW,
+        id: u16,
+    ) -> Self {
+        let config = model.config();
+        let mut transmitter = Self::from_config(TransmitterConfig {
+            pin,
+            delay,
+            now_fn,
+            id,
+        });
+        transmitter.set_protocol_timing(config.timing);
+        transmitter.set_preamble(config.preamble.as_slice());
+        transmitter.footer = config.footer;
+        transmitter.whitening = config.whitening;
+        transmitter.fec = config.fec;
+        transmitter
+    }
+
+    /// Creates a Transmitter using the default [PwmEncoder] from a plain
+    /// configuration, as an alternative to the [builder](Self::builder).
+    pub fn from_config(config: TransmitterConfig<'a, PIN, DELAY, NOW>) -> Self {
+        Transmitter {
+            pin: config.pin,
+            delay: config.delay,
+            now_fn: config.now_fn,
+            id: config.id,
+            whitening: None,
+            fec: Fec::None,
+            tx_jitter_us: 0,
+            inter_bit_gap_us: 0,
+            min_repeats: 1,
+            max_repeats: None,
+            inter_packet_gap: DEFAULT_INTER_PACKET_GAP,
+            preamble: DEFAULT_PREAMBLE,
+            footer: DEFAULT_FOOTER,
+            max_strength: None,
+            protocol_timing: ProtocolTiming::ch8803_default(),
+            encoder: PwmEncoder,
+            led: None,
+            busy: None,
+            timing_correction: 1.0,
+            #[cfg(feature = "observer")]
+            observer: None,
+            #[cfg(feature = "diagnostics")]
+            last_packet: None,
+        }
+    }
+}
+
+impl<'a, PIN, DELAY, NOW, ENC> Transmitter<'a, PIN, DELAY, NOW, ENC>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+{
+    /// Returns the pin, the delay, the `now_fn` and the ID in the order the
+    /// [builder](Self::builder) takes them, e.g. to use the pin for
+    /// something else. All other settings are dropped.
+    pub fn into_parts(self) -> (PIN, &'a RefCell<DELAY>, NOW, u16) {
+        (self.pin, self.delay, self.now_fn, self.id)
+    }
+}
+
+impl<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+    Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Replaces the bit encoding scheme, keeping all other settings.
+    pub fn with_encoder<E: Encoder>(
+        self,
+        encoder: E,
+    ) -> Transmitter<'a, PIN, DELAY, NOW, E, LED, BUSY> {
+        Transmitter {
+            pin: self.pin,
+            delay: self.delay,
+            now_fn: self.now_fn,
+            id: self.id,
+            whitening: self.whitening,
+            fec: self.fec,
+            tx_jitter_us: self.tx_jitter_us,
+            inter_bit_gap_us: self.inter_bit_gap_us,
+            min_repeats: self.min_repeats,
+            max_repeats: self.max_repeats,
+            inter_packet_gap: self.inter_packet_gap,
+            preamble: self.preamble,
+            footer: self.footer,
+            max_strength: self.max_strength,
+            protocol_timing: self.protocol_timing,
+            encoder,
+            led: self.led,
+            busy: self.busy,
+            timing_correction: self.timing_correction,
+            #[cfg(feature = "observer")]
+            observer: self.observer,
+            #[cfg(feature = "diagnostics")]
+            last_packet: self.last_packet,
+        }
+    }
+
+    /// Adds an LED that is on while a command is being sent, keeping all
+    /// other settings.
+    pub fn with_led<L: OutputPin>(
+        self,
+        led: L,
+    ) -> Transmitter<'a, PIN, DELAY, NOW, ENC, L, BUSY> {
+        Transmitter {
+            pin: self.pin,
+            delay: self.delay,
+            now_fn: self.now_fn,
+            id: self.id,
+            whitening: self.whitening,
+            fec: self.fec,
+            tx_jitter_us: self.tx_jitter_us,
+            inter_bit_gap_us: self.inter_bit_gap_us,
+            min_repeats: self.min_repeats,
+            max_repeats: self.max_repeats,
+            inter_packet_gap: self.inter_packet_gap,
+            preamble: self.preamble,
+            footer: self.footer,
+            max_strength: self.max_strength,
+            protocol_timing: self.protocol_timing,
+            encoder: self.encoder,
+            led: Some(LedIndicator(led)),
+            busy: self.busy,
+            timing_correction: self.timing_correction,
+            #[cfg(feature = "observer")]
+            observer: self.observer,
+            #[cfg(feature = "diagnostics")]
+            last_packet: self.last_packet,
+        }
+    }
+
+    /// Adds a pin that is high while a single repetition of a packet is
+    /// sent and low in between, keeping all other settings. On a logic
+    /// analyzer this shows the gaps between repetitions and the actual
+    /// transmission rate.
+    pub fn with_busy_pin<B: OutputPin>(
+        self,
+        busy: B,
+    ) -> Transmitter<'a, PIN, DELAY, NOW, ENC, LED, B> {
+        Transmitter {
+            pin: self.pin,
+            delay: self.delay,
+            now_fn: self.now_fn,
+            id: self.id,
+            whitening: self.whitening,
+            fec: self.fec,
+            tx_jitter_us: self.tx_jitter_us,
+            inter_bit_gap_us: self.inter_bit_gap_us,
+            min_repeats: self.min_repeats,
+            max_repeats: self.max_repeats,
+            inter_packet_gap: self.inter_packet_gap,
+            preamble: self.preamble,
+            footer: self.footer,
+            max_strength: self.max_strength,
+            protocol_timing: self.protocol_timing,
+            encoder: self.encoder,
+            led: self.led,
+            busy: Some(busy),
+            timing_correction: self.timing_correction,
+            #[cfg(feature = "observer")]
+            observer: self.observer,
+            #[cfg(feature = "diagnostics")]
+            last_packet: self.last_packet,
+        }
+    }
+
+    /// The number of channels a Transmitter can address, see [Channel::COUNT].
+    pub const fn channel_count() -> usize {
+        Channel::COUNT
+    }
+
+    /// Binds the Transmitter to a specific channel, allowing to send actual
+    /// commands. The channel can also be given as index, e.g. from user
+    /// input, which fails with [ChannelError] if it is out of range. A
+    /// [Channel] always converts, so `let Ok(collar) = ...` matches it.
+    #[allow(clippy::type_complexity)]
+    pub fn channel<'b, C: TryInto<Channel>>(
+        &'b mut self,
+        channel: C,
+    ) -> Result<
+        ChannelTransmitter<'a, 'b, PIN, DELAY, NOW, ENC, LED, BUSY>,
+        C::Error,
+    > {
+        Ok(ChannelTransmitter {
+            device: self,
+            channel: channel.try_into()?,
+            _channel: PhantomData,
+        })
+    }
+
+    /// Binds a Transmitter shared through a `RefCell` to a channel. Unlike
+    /// [channel](Self::channel), any number of the returned
+    /// [SharedChannelTransmitter]s can exist at the same time. Like there,
+    /// an index out of range fails with [ChannelError].
+    #[allow(clippy::type_complexity)]
+    pub fn channel_shared<'b, C: TryInto<Channel>>(
+        transmitter: &'b RefCell<Self>,
+        channel: C,
+    ) -> Result<
+        SharedChannelTransmitter<'a, 'b, PIN, DELAY, NOW, ENC, LED, BUSY>,
+        C::Error,
+    > {
+        Ok(SharedChannelTransmitter {
+            device: transmitter,
+            channel: channel.try_into()?,
+        })
+    }
+
+    /// Binds the Transmitter to a channel given as type, e.g.
+    /// `typed_channel::<Ch1>()`, so that the channel is part of the type of
+    /// the returned ChannelTransmitter.
+    pub fn typed_channel<'b, CH: FixedChannel>(
+        &'b mut self,
+    ) -> ChannelTransmitter<'a, 'b, PIN, DELAY, NOW, ENC, LED, BUSY, CH> {
+        ChannelTransmitter {
+            device: self,
+            channel: CH::CHANNEL,
+            _channel: PhantomData,
+        }
+    }
+
+    /// Sends an arbitrary payload wrapped in the standard preamble and footer
+    /// for the given duration. Each byte is encoded MSB first; no checksum is
+    /// added, so the caller has to include it in `payload`.
+    ///
+    /// The payload has to fit into [MAX_TIMINGS] together with the preamble
+    /// and footer, which allows 18 bytes with the default settings and the
+    /// [PwmEncoder], and fewer with encoders emitting more entries per bit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [PayloadError::TooLong] if the payload does not fit, and
+    /// [PayloadError::StrengthLimited] if a
+    /// [max_strength](Self::set_max_strength) is set, which cannot be
+    /// applied to a payload of unknown layout. Nothing is sent in both cases.
+    pub fn send_bytes(
+        &mut self,
+        payload: &[u8],
+        duration: impl Into<Duration>,
+    ) -> Result<(), PayloadError> {
+        if self.max_strength.is_some() {
+            return Err(PayloadError::StrengthLimited);
+        }
+
+        // Room for one more byte, so that it can be encoded before checking
+        let mut timings = [0; MAX_TIMINGS + 8 * 4];
+        let mut idx = 0;
+        let footer = self.footer.as_slice();
+
+        push_us(&mut timings, &mut idx, self.preamble.as_slice());
+        for &byte in payload {
+            self.trbits(byte, 8, &mut timings, &mut idx);
+            if idx + footer.len() > MAX_TIMINGS {
+                return Err(PayloadError::TooLong);
+            }
+        }
+        push_us(&mut timings, &mut idx, footer);
+
+        self.repeat(&timings[..idx], duration.into(), None, &no_inspection);
+        Ok(())
+    }
+
+    /// Sets a function that is called after every repetition of a command
+    /// packet, e.g. to log the commands or show them on a display.
+    #[cfg(all(feature = "observer", feature = "alloc"))]
+    pub fn set_packet_observer(
+        &mut self,
+        observer: impl Fn(&CommandPacket) + 'static,
+    ) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Sets a function that is called after every repetition of a command
+    /// packet, e.g. to log the commands or show them on a display.
+    #[cfg(all(feature = "observer", not(feature = "alloc")))]
+    pub fn set_packet_observer(
+        &mut self,
+        observer: &'static dyn Fn(&CommandPacket),
+    ) {
+        self.observer = Some(observer);
+    }
+
+    /// Captures the current configuration, e.g. to temporarily use another ID
+    /// and [restore](Self::restore) the old one afterwards.
+    pub fn checkpoint(&self) -> TransmitterSnapshot {
+        TransmitterSnapshot {
+            id: self.id,
+            whitening: self.whitening,
+            fec: self.fec,
+            tx_jitter_us: self.tx_jitter_us,
+            inter_bit_gap_us: self.inter_bit_gap_us,
+            min_repeats: self.min_repeats,
+            max_repeats: self.max_repeats,
+            inter_packet_gap: self.inter_packet_gap,
+            preamble: self.preamble,
+            footer: self.footer,
+            protocol_timing: self.protocol_timing,
+            max_strength: self.max_strength,
+        }
+    }
+
+    /// Applies a configuration captured by [checkpoint](Self::checkpoint).
+    pub fn restore(&mut self, snapshot: TransmitterSnapshot) {
+        self.id = snapshot.id;
+        self.whitening = snapshot.whitening;
+        self.fec = snapshot.fec;
+        self.tx_jitter_us = snapshot.tx_jitter_us;
+        self.inter_bit_gap_us = snapshot.inter_bit_gap_us;
+        self.min_repeats = snapshot.min_repeats;
+        self.max_repeats = snapshot.max_repeats;
+        self.inter_packet_gap = snapshot.inter_packet_gap;
+        self.preamble = snapshot.preamble;
+        self.footer = snapshot.footer;
+        self.protocol_timing = snapshot.protocol_timing;
+        self.max_strength = snapshot.max_strength;
+    }
+
+    /// Sets the whitening applied to the payload of command packets, or
+    /// disables it with `None`.
+    pub fn set_whitening(&mut self, whitening: Option<DataWhitening>) {
+        self.whitening = whitening;
+    }
+
+    /// Sets the error correction applied to the payload of command packets.
+    /// Stock CH8803 receivers only understand [Fec::None].
+    pub fn set_fec(&mut self, fec: Fec) {
+        self.fec = fec;
+    }
+
+    /// Waits for a pseudo-random time of up to `max_jitter_us` before every
+    /// repetition of a packet, so that transmitters sending at the same time
+    /// collide less often. The time is derived from the lower bits of
+    /// `now_fn` and extends the duration of the command. Zero disables it.
+    pub fn set_tx_jitter(&mut self, max_jitter_us: u16) {
+        self.tx_jitter_us = max_jitter_us;
+    }
+
+    /// Inserts a gap of `us` microseconds after every transition of the
+    /// pin, which holds the current level for that much longer, e.g. to
+    /// test receivers expecting a guard interval between symbols. This
+    /// changes the bit rate of everything sent and is not understood by
+    /// stock receivers. Zero disables it.
+    pub fn set_inter_bit_gap(&mut self, us: u16) {
+        self.inter_bit_gap_us = us;
+    }
+
+    /// Sends at least `min_repeats` repetitions of every packet, even if
+    /// the duration passed before, e.g. when it is shorter than a single
+    /// packet or `now_fn` has a coarse resolution. Defaults to 1.
+    pub fn set_min_repeats(&mut self, min_repeats: u8) {
+        self.min_repeats = min_repeats;
+    }
+
+    /// Sends at most `max_repeats` repetitions of every packet, even if the
+    /// duration has not passed yet, e.g. for receivers that malfunction
+    /// when receiving too many identical packets. The limit takes
+    /// precedence over [set_min_repeats](Self::set_min_repeats), and `None`
+    /// removes it.
+    pub fn set_max_repeats(&mut self, max_repeats: Option<u8>) {
+        self.max_repeats = max_repeats;
+    }
+
+    /// Holds the pin low for `gap` after every repetition of a packet.
+    /// Original remotes pause for about 10 ms between repetitions, the
+    /// default, and some receivers miss consecutive packets without the
+    /// pause because their gain control has not settled yet. The gap is
+    /// part of the duration of a command, so fewer repetitions are sent.
+    /// Zero sends repetitions back to back for the highest rate, which
+    /// should only be used with receivers verified to keep up.
+    pub fn set_inter_packet_gap(&mut self, gap: Duration) {
+        self.inter_packet_gap = gap;
+    }
+
+    /// Replaces the preamble sent before every packet, e.g. for clones of
+    /// the remote that use a different one. The entries are pulse lengths
+    /// in microseconds, alternating between low and high and starting low.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `preamble` has more than [MAX_PREAMBLE] entries, or an even
+    /// number of entries, which would end it high and invert the bits
+    /// following it.
+    pub fn set_preamble(&mut self, preamble: &[u16]) {
+        self.preamble = preamble_sequence(preamble);
+    }
+
+    /// Replaces the footer sent after every packet, which ends it and
+    /// separates it from the next repetition. The entries are pulse lengths
+    /// in microseconds, starting with the level following the last bit,
+    /// i.e. high for the [PwmEncoder].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `footer` has more than [MAX_FOOTER] entries.
+    pub fn set_footer(&mut self, footer: &[u16]) {
+        self.footer = TimingSequence::new(footer);
+    }
+
+    /// Limits the strength of every command sent to `cap`, e.g. to prevent
+    /// an accidental `shock_ms(255, 5000)`. Higher strengths are lowered to
+    /// the cap instead of being sent; beeps are not affected.
+    pub fn set_max_strength(&mut self, cap: u8) {
+        self.max_strength = Some(cap);
+    }
+
+    /// Removes the limit set with [set_max_strength](Self::set_max_strength).
+    pub fn clear_max_strength(&mut self) {
+        self.max_strength = None;
+    }
+
+    /// The strength every command is limited to, if any.
+    pub fn max_strength(&self) -> Option<u8> {
+        self.max_strength
+    }
+
+    /// Modulates the high pulses with a carrier of `carrier_hz` and a duty
+    /// cycle of `duty_pct` percent, for transmitter modules that need it,
+    /// or disables it with a `carrier_hz` of zero. The carrier is generated
+    /// by toggling the pin in software, so every period costs two calls of
+    /// the delay and only a few kHz are practical.
+    pub fn set_carrier(&mut self, carrier_hz: u32, duty_pct: u8) {
+        self.protocol_timing.carrier_hz = carrier_hz;
+        self.protocol_timing.carrier_duty_pct = duty_pct;
+    }
+
+    /// Replaces the pulse lengths of the bits and the preamble and footer
+    /// with those of `timing`, for clones of the remote with different
+    /// timings. Only the [PwmEncoder] uses the bit timings; the
+    /// [ManchesterEncoder](crate::encoder::ManchesterEncoder) and
+    /// [BmcEncoder](crate::encoder::BmcEncoder) ignore them and keep theirs.
+    pub fn set_protocol_timing(&mut self, timing: ProtocolTiming) {
+        self.protocol_timing = timing;
+        self.preamble = timing.preamble();
+        self.footer = timing.footer();
+    }
+
+    /// The ID of the device.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// The time one repetition of a command packet takes with the current
+    /// settings, in microseconds: the preamble, the bits as encoded by the
+    /// encoder and error correction, the footer, the inter-bit gaps and the
+    /// [inter-packet gap](Self::set_inter_packet_gap) following it. Dividing
+    /// the duration of a command by it estimates the number of repetitions
+    /// sent. The random delay of [set_tx_jitter](Self::set_tx_jitter) is not
+    /// included.
+    pub fn packet_period_us(&self) -> u32 {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: Channel::Channel1,
+            command: Command::Beep,
+            strength: 0,
+        };
+        let mut timings = [0; MAX_TIMINGS];
+        let len = self.encode_into(&packet, &mut timings);
+
+        let mut ticks = 0;
+        let mut edges = 0;
+        for &timing in timings[..len].iter().filter(|&&t| t != 0) {
+            ticks += u64::from(timing);
+            edges += 1;
+        }
+        let micros = ticks / TIMING_PER_US
+            + edges * u64::from(self.inter_bit_gap_us)
+            + u64::from(self.inter_packet_gap.to_micros());
+        u32::try_from(micros).unwrap_or(u32::MAX)
+    }
+
+    /// The most and least significant byte of the ID, in the order they
+    /// are sent.
+    pub fn id_bytes(&self) -> (u8, u8) {
+        id_bytes(self.id)
+    }
+
+    /// Records the edges of the signal on `input_pin`, e.g. from a receiver
+    /// module picking up the original remote, by polling it until it stays
+    /// idle for the timeout of `capture` or `capture` is full. Timestamps
+    /// come from the `now_fn` of the Transmitter. Returns the number of
+    /// recorded edges.
+    ///
+    /// Polling adds jitter of a few microseconds per edge. Callers needing
+    /// better resolution should record the edges from an interrupt instead.
+    pub fn sniff_mode<IP: InputPin, const N: usize>(
+        &mut self,
+        mut input_pin: IP,
+        capture: &mut SignalCapture<N>,
+    ) -> Result<usize, IP::Error> {
+        capture.clear();
+        let mut level = input_pin.is_high()?;
+        capture.initial_level = level;
+
+        let mut last_edge = (self.now_fn)();
+        let timeout = u64::from(capture.idle_timeout.to_micros());
+        while !capture.is_full() {
+            let high = input_pin.is_high()?;
+            let now = (self.now_fn)();
+            if high != level {
+                capture.push(now);
+                level = high;
+                last_edge = now;
+            } else if (now - last_edge).to_micros() >= timeout {
+                break;
+            }
+        }
+        Ok(capture.len())
+    }
+
+    /// Replays the signal in `capture` for the given duration, e.g. to act
+    /// as the remote of a collar whose protocol is unknown.
+    ///
+    /// # Warning
+    ///
+    /// The replayed signal is sent as-is, so the Transmitter can neither
+    /// limit the strength nor tell what it makes the receiver do. A capture
+    /// of a shock will keep shocking for the whole duration, and a capture
+    /// containing noise may trigger arbitrary commands. Only replay
+    /// captures whose effect has been verified at a low strength.
+    pub fn clone_remote<const N: usize>(
+        &mut self,
+        capture: &SignalCapture<N>,
+        duration: impl Into<Duration>,
+    ) -> Result<(), TransmitError> {
+        let mut timings = [0; N];
+        let len = capture.to_timings(&mut timings);
+        let transmission =
+            self.repeat(&timings[..len], duration.into(), None, &no_inspection);
+        transmission.error.map_or(Ok(()), Err)
+    }
+
+    /// Sends pulse widths in microseconds once, alternating low and high and
+    /// starting low, e.g. a frame recorded by a
+    /// [RawCapture](crate::capture::RawCapture). Unlike
+    /// [send_pulse_train](Self::send_pulse_train), nothing of the
+    /// configuration is applied: no carrier, no inter-bit gap and no
+    /// repetitions. Only the LED and busy pin are driven as usual.
+    ///
+    /// # Warning
+    ///
+    /// Like [clone_remote](Self::clone_remote), the Transmitter cannot tell
+    /// what the replayed signal makes the receiver do.
+    pub fn send_timing_raw(
+        &mut self,
+        timings: &[u16],
+    ) -> Result<(), TransmitError> {
+        if let Some(led) = &mut self.led {
+            led.on();
+        }
+        if let Some(busy) = &mut self.busy {
+            let _ = busy.set_high();
+        }
+
+        let mut result = Ok(());
+        for (i, &width) in timings.iter().enumerate() {
+            if width == 0 {
+                continue;
+            }
+            let set = if i % 2 == 1 {
+                self.pin.set_high()
+            } else {
+                self.pin.set_low()
+            };
+            if set.is_err() {
+                result = Err(TransmitError::Pin);
+            }
+            self.wait_micros(width.into());
+        }
+        if self.pin.set_low().is_err() {
+            result = Err(TransmitError::Pin);
+        }
+
+        if let Some(busy) = &mut self.busy {
+            let _ = busy.set_low();
+        }
+        if let Some(led) = &mut self.led {
+            led.off();
+        }
+        result
+    }
+
+    /// Sends `train` repeatedly for the given duration, e.g. while developing
+    /// support for a new receiver. Nothing about the waveform is checked.
+    pub fn send_pulse_train<const N: usize>(
+        &mut self,
+        train: &PulseTrain<N>,
+        duration: Duration,
+    ) -> Result<(), TransmitError> {
+        let transmission =
+            self.repeat(train.as_slice(), duration, None, &no_inspection);
+        transmission.error.map_or(Ok(()), Err)
+    }
+
+    /// Sends a packet to the receiver for the given duration. Unlike the
+    /// commands of [ChannelTransmitter], this uses the ID stored in the packet
+    /// instead of the one of the Transmitter.
+    pub fn send_packet(
+        &mut self,
+        packet: &CommandPacket,
+        duration: impl Into<Duration>,
+    ) {
+        self.send_packet_inspected(packet, duration.into(), &no_inspection);
+    }
+
+    /// Sends a command like the methods of [ChannelTransmitter] and returns
+    /// a record of the transmission, e.g. for an audit log. Fails with
+    /// [ChannelError] if the channel is an index out of range.
+    #[cfg(feature = "diagnostics")]
+    pub fn send_command_recorded<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> Result<CommandRecord, C::Error> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: channel.try_into()?,
+            command,
+            strength,
+        };
+
+        let sent_at = (self.now_fn)();
+        let transmission = self.send_packet_inspected(
+            &packet,
+            duration.into(),
+            &no_inspection,
+        );
+        let elapsed = (self.now_fn)() - sent_at;
+
+        Ok(CommandRecord {
+            packet,
+            sent_at,
+            repetitions: transmission.repetitions,
+            actual_duration: Duration::from_ticks(elapsed.ticks() as u32),
+            error: transmission.error,
+        })
+    }
+
+    pub(crate) fn send_packet_inspected(
+        &mut self,
+        packet: &CommandPacket,
+        duration: Duration,
+        inspector: &impl PacketInspector,
+    ) -> Transmission {
+        let timings = self.encode_command_packet(packet);
+        #[cfg(feature = "diagnostics")]
+        {
+            self.last_packet = Some(*packet);
+        }
+
+        self.repeat(timings.as_slice(), duration, Some(packet), inspector)
+    }
+
+    /// Sends a command to all channels of the device for the given
+    /// duration, switching to the next channel with every repetition. This
+    /// reaches a receiver paired with the ID of the Transmitter even if its
+    /// channel is unknown, at a third of the usual repetition rate. All
+    /// repetition settings apply as usual, counting the packets of all
+    /// channels together.
+    pub fn send_hopping(
+        &mut self,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) {
+        let mut timings = [[0; MAX_TIMINGS]; Channel::COUNT];
+        let mut lens = [0; Channel::COUNT];
+        let mut packets = [CommandPacket {
+            id: self.id,
+            channel: Channel::Channel1,
+            command,
+            strength,
+        }; Channel::COUNT];
+        for (i, (packet, (timings, len))) in packets
+            .iter_mut()
+            .zip(timings.iter_mut().zip(&mut lens))
+            .enumerate()
+        {
+            packet.channel = Channel::Channel1 + i as u8;
+            *len = self.encode_into(packet, timings);
+        }
+
+        let frames: [Frame<'_>; Channel::COUNT] = core::array::from_fn(|i| {
+            (&timings[i][..lens[i]], Some(&packets[i]))
+        });
+        self.repeat_cycle(&frames, duration.into(), &no_inspection);
+    }
+
+    /// Checks the accuracy of the delay by encoding the last packet sent for
+    /// a duration again and timing the delays of one repetition with
+    /// `now_fn`, without touching the pin. Returns `None` if no packet was
+    /// sent yet.
+    #[cfg(feature = "diagnostics")]
+    pub fn verify_timing(&self) -> Option<TimingReport> {
+        let packet = self.last_packet?;
+        let mut timings = [0; MAX_TIMINGS];
+        let len = self.encode_into(&packet, &mut timings);
+
+        let start = (self.now_fn)();
+        let mut delay = self.delay.borrow_mut();
+        for &duration in timings[..len].iter().filter(|&&d| d != 0) {
+            delay.delay_pulse(duration);
+        }
+        drop(delay);
+        let measured_us = ((self.now_fn)() - start).to_micros();
+
+        let total: u64 = timings[..len].iter().map(|&t| u64::from(t)).sum();
+        let expected_us = total / TIMING_PER_US;
+        let deviation = (measured_us as i64 - expected_us as i64) * 100
+            / expected_us.max(1) as i64;
+        Some(TimingReport {
+            expected_us,
+            measured_us,
+            deviation_pct: deviation.clamp(i8::MIN.into(), i8::MAX.into())
+                as i8,
+        })
+    }
+
+    /// Sends every packet exactly once, followed by the
+    /// [inter-packet gap](Self::set_inter_packet_gap). Packets repeated
+    /// within four distinct packets reuse their timings instead of being
+    /// encoded again.
+    ///
+    /// Unlike the other commands, this does not repeat the packets for a
+    /// duration, so it is mostly useful for long sequences of different
+    /// packets, e.g. a recorded pattern.
+    pub fn send_batch(&mut self, packets: &[CommandPacket]) -> BatchResult {
+        let gap = self.inter_packet_gap.to_micros();
+        self.send_batch_by(packets.len(), |i| (packets[i], gap))
+    }
+
+    /// Like [send_batch](Self::send_batch), waiting for the given number of
+    /// microseconds after each packet instead of the inter-packet gap, e.g.
+    /// for a shock immediately followed by a beep. The gap after the last
+    /// packet is ignored.
+    ///
+    /// Every packet is encoded before waiting for the gap of the previous
+    /// one, so the time spent encoding counts towards the gap.
+    pub fn send_batch_with_gaps(
+        &mut self,
+        packets: &[(CommandPacket, u32)],
+    ) -> BatchResult {
+        self.send_batch_by(packets.len(), |i| packets[i])
+    }
+
+    /// Sends `count` packets, each followed by a gap in microseconds, as
+    /// returned by `item` for its index.
+    fn send_batch_by(
+        &mut self,
+        count: usize,
+        item: impl Fn(usize) -> (CommandPacket, u32),
+    ) -> BatchResult {
+        let mut result = BatchResult {
+            sent: 0,
+            first_error: None,
+        };
+        let mut cache = BatchCache::new();
+        let mut gap_start: Option<(Instant, u32)> = None;
+
+        if let Some(led) = &mut self.led {
+            led.on();
+        }
+
+        for i in 0..count {
+            let (packet, gap) = item(i);
+            let timings =
+                cache.get_or_encode(&packet, |t| self.encode_into(&packet, t));
+
+            if let Some((start, gap)) = gap_start {
+                let elapsed = ((self.now_fn)() - start).to_micros();
+                let elapsed = u32::try_from(elapsed).unwrap_or(u32::MAX);
+                self.wait_micros(gap.saturating_sub(elapsed));
+            }
+
+            match self.send_timing(timings) {
+                Ok(()) => result.sent += 1,
+                Err(_) => {
+                    result.first_error.get_or_insert(i);
+                }
+            }
+
+            #[cfg(feature = "observer")]
+            if let Some(observer) = &self.observer {
+                observer(&packet);
+            }
+
+            gap_start = Some(((self.now_fn)(), gap));
+        }
+
+        if let Some(led) = &mut self.led {
+            led.off();
+        }
+
+        result
+    }
+
+    /// Sends a command like the methods of [ChannelTransmitter] and returns
+    /// the number of packets sent without errors, e.g. to find out how many
+    /// repetitions a receiver needs. Fails with [ChannelError] if the
+    /// channel is an index out of range.
+    pub fn send_command<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> Result<u32, C::Error> {
+        let channel = channel.try_into()?;
+        let duration = duration.into();
+        #[cfg(feature = "rtt-logging")]
+        rtt_target::rprintln!(
+            "{:?} {:?} strength {} for {}",
+            channel,
+            command,
+            strength,
+            duration.display()
+        );
+
+        let packet = CommandPacket {
+            id: self.id,
+            channel,
+            command,
+            strength,
+        };
+        Ok(self
+            .send_packet_inspected(&packet, duration, &no_inspection)
+            .sent)
+    }
+
+    /// Sends a command to every channel in turn, e.g. to several collars
+    /// paired to the channels of the same ID. Each channel receives the
+    /// command for the full `duration` before the next one starts, so the
+    /// transmission takes three times as long. Returns the number of packets
+    /// sent without errors.
+    pub fn broadcast(
+        &mut self,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> u32 {
+        self.broadcast_channels(&Channel::ALL, command, strength, duration)
+    }
+
+    /// Like [broadcast](Self::broadcast), sending only to `channels` in the
+    /// given order, which takes `channels.len()` times the `duration`.
+    pub fn broadcast_channels(
+        &mut self,
+        channels: &[Channel],
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> u32 {
+        let duration = duration.into();
+        channels
+            .iter()
+            .map(|&channel| {
+                let Ok(sent) =
+                    self.send_command(channel, command, strength, duration);
+                sent
+            })
+            .sum()
+    }
+
+    /// Sends a [TimedCommand] like [send_command](Self::send_command) and
+    /// returns the number of packets sent without errors.
+    pub fn execute(&mut self, command: &TimedCommand) -> u32 {
+        let Ok(sent) = self.send_command(
+            command.channel,
+            command.command,
+            command.strength,
+            Duration::millis(command.duration_ms),
+        );
+        sent
+    }
+
+    /// Sends a command with exactly `repetitions` repetitions of the packet
+    /// instead of repeating it for a duration, e.g. to match the number of
+    /// packets of an original remote. The limits of
+    /// [set_min_repeats](Self::set_min_repeats) and
+    /// [set_max_repeats](Self::set_max_repeats) do not apply. Returns the
+    /// number of packets sent without errors, or [ChannelError] if the
+    /// channel is an index out of range.
+    pub fn send_command_n<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+        command: Command,
+        strength: u8,
+        repetitions: u16,
+    ) -> Result<u32, C::Error> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: channel.try_into()?,
+            command,
+            strength,
+        };
+        let timings = self.encode_command_packet(&packet);
+        #[cfg(feature = "diagnostics")]
+        {
+            self.last_packet = Some(packet);
+        }
+
+        Ok(self
+            .repeat_n(
+                timings.as_slice(),
+                u32::from(repetitions),
+                Some(&packet),
+                &no_inspection,
+            )
+            .sent)
+    }
+
+    fn repeat(
+        &mut self,
+        timings: &[Timing],
+        duration: Duration,
+        packet: Option<&CommandPacket>,
+        inspector: &impl PacketInspector,
+    ) -> Transmission {
+        self.repeat_cycle(&[(timings, packet)], duration, inspector)
+    }
+
+    /// Like [repeat](Self::repeat), sending the `frames` in turn, one per
+    /// repetition.
+    fn repeat_cycle(
+        &mut self,
+        frames: &[Frame<'_>],
+        duration: Duration,
+        inspector: &impl PacketInspector,
+    ) -> Transmission {
+        let mut transmission = Transmission {
+            repetitions: 0,
+            sent: 0,
+            error: None,
+        };
+
+        if let Some(led) = &mut self.led {
+            led.on();
+        }
+
+        let mut end = (self.now_fn)() + self.corrected(duration);
+        let max = self.max_repeats.map_or(u32::MAX, u32::from);
+        while transmission.repetitions < max
+            && (transmission.repetitions < u32::from(self.min_repeats)
+                || (self.now_fn)() < end)
+        {
+            let frame = transmission.repetitions as usize % frames.len();
+            let (timings, packet) = frames[frame];
+            let jitter = self.send_repetition(
+                timings,
+                packet,
+                inspector,
+                &mut transmission,
+            );
+            end += Duration::micros(jitter);
+        }
+
+        if let Some(led) = &mut self.led {
+            led.off();
+        }
+
+        transmission
+    }
+
+    /// Like [repeat](Self::repeat), sending exactly `repetitions`
+    /// repetitions regardless of the time they take.
+    fn repeat_n(
+        &mut self,
+        timings: &[Timing],
+        repetitions: u32,
+        packet: Option<&CommandPacket>,
+        inspector: &impl PacketInspector,
+    ) -> Transmission {
+        let mut transmission = Transmission {
+            repetitions: 0,
+            sent: 0,
+            error: None,
+        };
+
+        if let Some(led) = &mut self.led {
+            led.on();
+        }
+
+        for _ in 0..repetitions {
+            self.send_repetition(timings, packet, inspector, &mut transmission);
+        }
+
+        if let Some(led) = &mut self.led {
+            led.off();
+        }
+
+        transmission
+    }
+
+    /// Sends a single repetition after the random delay of
+    /// [set_tx_jitter](Self::set_tx_jitter) and returns the delay in
+    /// microseconds.
+    fn send_repetition(
+        &mut self,
+        timings: &[Timing],
+        #[cfg_attr(not(feature = "observer"), allow(unused_variables))]
+        packet: Option<&CommandPacket>,
+        inspector: &impl PacketInspector,
+        transmission: &mut Transmission,
+    ) -> u32 {
+        let mut jitter = 0;
+        if self.tx_jitter_us > 0 {
+            jitter = self.jitter_us();
+            self.wait_micros(jitter);
+        }
+
+        inspector(Phase::BeforeTransmit, timings);
+        match self.send_timing(timings) {
+            Ok(()) => transmission.sent += 1,
+            Err(err) => {
+                transmission.error.get_or_insert(err);
+            }
+        }
+        transmission.repetitions += 1;
+        inspector(Phase::AfterTransmit, timings);
+        // send_timing leaves the pin low
+        self.wait_micros(self.inter_packet_gap.to_micros());
+
+        #[cfg(feature = "observer")]
+        if let (Some(observer), Some(packet)) = (&self.observer, packet) {
+            observer(packet);
+        }
+
+        jitter
+    }
+
+    /// Sends `timings` once, without the CH8803 preamble and footer, for
+    /// other protocols sharing the Transmitter.
+    pub(crate) fn send_raw(
+        &mut self,
+        timings: &[Timing],
+    ) -> Result<(), TransmitError> {
+        self.send_raw_with(timings, |_, _| {})
+    }
+
+    /// Like [send_raw](Self::send_raw), calling `on_edge` with the index of
+    /// every non-zero entry and the `now_fn` just before its level is set.
+    pub(crate) fn send_raw_with(
+        &mut self,
+        timings: &[Timing],
+        on_edge: impl FnMut(usize, &NOW),
+    ) -> Result<(), TransmitError> {
+        if let Some(led) = &mut self.led {
+            led.on();
+        }
+        let result = self.send_timing_with(timings, on_edge);
+        if let Some(led) = &mut self.led {
+            led.off();
+        }
+        result
+    }
+
+    /// Divides `duration` by the `timing_correction`.
+    fn corrected(&self, duration: Duration) -> Duration {
+        if self.timing_correction == 1.0 {
+            return duration;
+        }
+        let micros = duration.to_micros() as f32 / self.timing_correction;
+        Duration::micros(micros as u32)
+    }
+
+    /// A pseudo-random delay of up to `tx_jitter_us`, from the lower bits of
+    /// the current time.
+    fn jitter_us(&self) -> u32 {
+        let ticks = (self.now_fn)().ticks();
+        (ticks % (u64::from(self.tx_jitter_us) + 1)) as u32
+    }
+
+    /// Blocks for `micros` using the delay, in steps that fit a [Timing].
+    pub(crate) fn wait_micros(&mut self, mut micros: u32) {
+        while micros > 0 {
+            let step = micros.min(u32::from(u16::MAX)) as u16;
+            self.delay.borrow_mut().delay_pulse(us(step));
+            micros -= u32::from(step);
+        }
+    }
+
+    fn send_timing(&mut self, timings: &[Timing]) -> Result<(), TransmitError> {
+        self.send_timing_with(timings, |_, _| {})
+    }
+
+    fn send_timing_with(
+        &mut self,
+        timings: &[Timing],
+        mut on_edge: impl FnMut(usize, &NOW),
+    ) -> Result<(), TransmitError> {
+        if let Some(busy) = &mut self.busy {
+            let _ = busy.set_high();
+        }
+
+        let mut result = Ok(());
+        let mut level = false;
+        for (i, &duration) in timings.iter().enumerate() {
+            if duration == 0 {
+                level = !level;
+                continue;
+            }
+
+            on_edge(i, &self.now_fn);
+            let carrier = level && self.protocol_timing.carrier_hz > 0;
+            let set = if carrier {
+                self.send_carrier(duration)
+            } else if level {
+                self.pin.set_high()
+            } else {
+                self.pin.set_low()
+            };
+            if set.is_err() {
+                result = Err(TransmitError::Pin);
+            }
+            let mut delay = self.delay.borrow_mut();
+            if !carrier {
+                delay.delay_pulse(duration);
+            }
+            if self.inter_bit_gap_us > 0 {
+                delay.delay_pulse(us(self.inter_bit_gap_us));
+            }
+            drop(delay);
+            level = !level;
+        }
+        if self.pin.set_low().is_err() {
+            result = Err(TransmitError::Pin);
+        }
+
+        if let Some(busy) = &mut self.busy {
+            let _ = busy.set_low();
+        }
+
+        result
+    }
+
+    /// Toggles the pin with the carrier of the
+    /// [protocol_timing](Self::set_protocol_timing) for `duration`, starting
+    /// high. Returns the last error of the pin, if any.
+    fn send_carrier(&mut self, duration: Timing) -> Result<(), PIN::Error> {
+        let hz = u64::from(self.protocol_timing.carrier_hz);
+        let duty = u64::from(self.protocol_timing.carrier_duty_pct);
+        let period = (1_000_000 * TIMING_PER_US / hz).max(2);
+        let high = (period * duty / 100).clamp(1, period - 1);
+
+        let mut result = Ok(());
+        let mut remaining = u64::from(duration);
+        while remaining > 0 {
+            let on = high.min(remaining);
+            if let Err(err) = self.pin.set_high() {
+                result = Err(err);
+            }
+            self.delay.borrow_mut().delay_pulse(on as Timing);
+            remaining -= on;
+
+            let off = (period - high).min(remaining);
+            if off == 0 {
+                break;
+            }
+            if let Err(err) = self.pin.set_low() {
+                result = Err(err);
+            }
+            self.delay.borrow_mut().delay_pulse(off as Timing);
+            remaining -= off;
+        }
+        result
+    }
+
+    /// The timings the Transmitter sends for a command with the current
+    /// settings, without sending it, e.g. to check the encoding on the host.
+    pub fn encode_packet(
+        &self,
+        channel: Channel,
+        command: Command,
+        strength: u8,
+    ) -> PacketTimings {
+        self.encode_command_packet(&CommandPacket {
+            id: self.id,
+            channel,
+            command,
+            strength,
+        })
+    }
+
+    /// Like [encode_packet](Self::encode_packet), using the ID of the packet.
+    pub fn encode_command_packet(
+        &self,
+        packet: &CommandPacket,
+    ) -> PacketTimings {
+        let mut timings = PacketTimings {
+            timings: [0; MAX_TIMINGS],
+            len: 0,
+        };
+        timings.len = self.encode_into(packet, &mut timings.timings);
+        timings
+    }
+
+    /// Writes the timings of a complete packet and returns their count. The
+    /// strength is limited to the [max_strength](Self::max_strength).
+    pub(crate) fn encode_into(
+        &self,
+        packet: &CommandPacket,
+        timings: &mut [Timing; MAX_TIMINGS],
+    ) -> usize {
+        let mut packet = *packet;
+        if let Some(cap) = self.max_strength
+            && packet.command != Command::Beep
+        {
+            packet.strength = packet.strength.min(cap);
+        }
+
+        encode(
+            &packet,
+            self.preamble.as_slice(),
+            self.footer.as_slice(),
+            self.whitening.as_ref(),
+            self.fec,
+            &self.encoder,
+            &self.protocol_timing,
+            timings,
+        )
+    }
+
+    fn trbits(
+        &self,
+        val: impl Into<u16>,
+        bits: u8,
+        timings: &mut [Timing],
+        idx: &mut usize,
+    ) {
+        encode_bits(
+            &self.encoder,
+            &self.protocol_timing,
+            val.into(),
+            bits,
+            timings,
+            idx,
+        );
+    }
+}
+
+/// Derives a stable ID from a chip-unique value, e.g. the unique ID of the
+/// flash chip of an RP2040, so that every device gets its own ID without
+/// hardcoding one. The seed is hashed with FNV-1a and folded into the range
+/// `1..=0xFFFF`, leaving out zero, which some receivers treat as broadcast.
+pub fn id_from_seed(seed: u64) -> u16 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = seed.to_le_bytes().iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    let folded = hash ^ hash >> 16 ^ hash >> 32 ^ hash >> 48;
+    (folded % 0xffff) as u16 + 1
+}
+
+/// The checksum of a packet: the sum of the ID bytes, the channel, the
+/// command and the strength, wrapping on overflow.
+pub fn encode_checksum(
+    id: u16,
+    channel: Channel,
+    command: Command,
+    strength: u8,
+) -> u8 {
+    let (msb, lsb) = id_bytes(id);
+    msb.wrapping_add(lsb)
+        .wrapping_add(channel as u8)
+        .wrapping_add(command as u8)
+        .wrapping_add(strength)
+}
+
+/// Writes the 42 bits of a packet without whitening or error correction as
+/// uppercase hexadecimal ASCII digits into `out` and returns their number,
+/// e.g. to print them over a serial port with
+/// `core::str::from_utf8(&out[..len])`. The bits are padded with two zeros
+/// at the top to 11 digits.
+pub fn encode_packet_hex(
+    id: u16,
+    channel: Channel,
+    command: Command,
+    strength: u8,
+    out: &mut [u8; 32],
+) -> usize {
+    const DIGITS: usize = 11;
+    let bits = CommandPacket {
+        id,
+        channel,
+        command,
+        strength,
+    }
+    .to_bits();
+
+    for (i, digit) in out[..DIGITS].iter_mut().enumerate() {
+        let nibble = (bits >> (4 * (DIGITS - 1 - i))) & 0xf;
+        *digit = b"0123456789ABCDEF"[nibble as usize];
+    }
+    DIGITS
+}
+
+/// Writes the timings of a packet into `timings` and returns their number.
+#[allow(clippy::too_many_arguments)]
+fn encode(
+    packet: &CommandPacket,
+    preamble: &[u16],
+    footer: &[u16],
+    whitening: Option<&DataWhitening>,
+    fec: Fec,
+    encoder: &impl Encoder,
+    protocol_timing: &ProtocolTiming,
+    timings: &mut [Timing; MAX_TIMINGS],
+) -> usize {
+    let CommandPacket {
+        channel,
+        command,
+        strength,
+        ..
+    } = *packet;
+    let (msb, lsb) = packet.id_bytes();
+    let mut payload = [
+        msb,
+        lsb,
+        (channel as u8) << 4 | command as u8,
+        strength,
+        packet.checksum(),
+    ];
+    if let Some(whitening) = whitening {
+        whitening.apply(&mut payload);
+    }
+
+    let mut idx = 0;
+    push_us(timings, &mut idx, preamble);
+    for byte in payload {
+        let (bits, count) = fec.encode_byte(byte);
+        encode_bits(encoder, protocol_timing, bits, count, timings, &mut idx);
+    }
+    encode_bits(encoder, protocol_timing, 0, 2, timings, &mut idx);
+    push_us(timings, &mut idx, footer);
+
+    idx
+}
+
+/// Encodes the lowest `bits` bits of `val`, MSB first.
+fn encode_bits(
+    encoder: &impl Encoder,
+    protocol_timing: &ProtocolTiming,
+    val: u16,
+    bits: u8,
+    timings: &mut [Timing],
+    idx: &mut usize,
+) {
+    for i in (0..bits).rev() {
+        let bit_set = (val >> i) & 1 != 0;
+        encoder.encode_bit_timed(bit_set, protocol_timing, timings, idx);
+    }
+}
+
+fn push(timings: &mut [Timing], idx: &mut usize, values: &[Timing]) {
+    timings[*idx..*idx + values.len()].copy_from_slice(values);
+    *idx += values.len();
+}
+
+/// Checks the requirements of [Transmitter::set_preamble].
+pub(crate) fn preamble_sequence(
+    preamble: &[u16],
+) -> TimingSequence<MAX_PREAMBLE> {
+    assert!(
+        preamble.len() % 2 == 1,
+        "the preamble has to end with the low level"
+    );
+    TimingSequence::new(preamble)
+}
+
+fn id_bytes(id: u16) -> (u8, u8) {
+    ((id >> 8) as u8, id as u8)
+}
+
+/// Like [push], for values in microseconds.
+fn push_us(timings: &mut [Timing], idx: &mut usize, values: &[u16]) {
+    for &value in values {
+        timings[*idx] = us(value);
+        *idx += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::NoPin;
+    use crate::timing::{self, MockTimingSource, TimingSource};
+
+    #[test]
+    fn max_strength_boundary() {
+        let source = RefCell::new(MockTimingSource::default());
+        let uncapped = timing::transmitter(NoPin, &source, 0x0d25);
+        let mut capped = timing::transmitter(NoPin, &source, 0x0d25);
+        capped.set_max_strength(100);
+
+        let channel = Channel::Channel1;
+        for (strength, sent) in [(99, 99), (100, 100), (101, 100)] {
+            assert_eq!(
+                capped.encode_packet(channel, Command::Shock, strength),
+                uncapped.encode_packet(channel, Command::Shock, sent),
+                "strength {strength}",
+            );
+        }
+        assert_eq!(
+            capped.encode_packet(channel, Command::Beep, 101),
+            uncapped.encode_packet(channel, Command::Beep, 101),
+        );
+    }
+
+    fn packet(channel: Channel, command: Command) -> CommandPacket {
+        CommandPacket {
+            id: 0x0d25,
+            channel,
+            command,
+            strength: 50,
+        }
+    }
+
+    #[test]
+    fn batch_cache_reuses_repeated_packets() {
+        let shock = packet(Channel::Channel1, Command::Shock);
+        let beep = packet(Channel::Channel2, Command::Beep);
+        let mut cache = BatchCache::new();
+        let mut encoded = 0;
+        for packet in [shock, beep, shock, shock, beep] {
+            let timings = cache.get_or_encode(&packet, |timings| {
+                encoded += 1;
+                packet.encode(timings)
+            });
+            let mut expected = [0; MAX_TIMINGS];
+            let len = packet.encode(&mut expected);
+            assert_eq!(timings, &expected[..len]);
+        }
+        assert_eq!(encoded, 2);
+    }
+
+    #[test]
+    fn batch_cache_replaces_oldest_packet() {
+        let mut cache = BatchCache::new();
+        let mut encoded = 0;
+        let mut send = |strength| {
+            let packet = CommandPacket {
+                strength,
+                ..packet(Channel::Channel1, Command::Vibrate)
+            };
+            cache.get_or_encode(&packet, |timings| {
+                encoded += 1;
+                packet.encode(timings)
+            });
+        };
+        // 1 is replaced by 5, so only 2 to 4 are still cached
+        for strength in [1, 2, 3, 4, 5, 2, 3, 4, 1] {
+            send(strength);
+        }
+        assert_eq!(encoded, 6);
+    }
+
+    #[test]
+    fn send_batch_waits_for_gaps() {
+        let shock = packet(Channel::Channel1, Command::Shock);
+        let beep = packet(Channel::Channel1, Command::Beep);
+        let source = RefCell::new(MockTimingSource::default());
+        let mut transmitter = timing::transmitter(NoPin, &source, 0x0d25);
+        // Every bit takes as long, so all packets do
+        let timings = transmitter.encode_command_packet(&shock);
+        let packet_us = timings
+            .as_slice()
+            .iter()
+            .map(|&t| u64::from(t))
+            .sum::<u64>()
+            / TIMING_PER_US;
+
+        let start = source.borrow().now();
+        let result = transmitter.send_batch(&[shock, beep, shock]);
+        assert_eq!(
+            result,
+            BatchResult {
+                sent: 3,
+                first_error: None
+            }
+        );
+        let elapsed_us = (source.borrow().now() - start).to_micros();
+        let gap_us = u64::from(DEFAULT_INTER_PACKET_GAP.to_micros());
+        assert_eq!(elapsed_us, 3 * packet_us + 2 * gap_us);
+
+        let start = source.borrow().now();
+        let result =
+            transmitter.send_batch_with_gaps(&[(shock, 500), (beep, 500)]);
+        assert_eq!(
+            result,
+            BatchResult {
+                sent: 2,
+                first_error: None
+            }
+        );
+        let elapsed_us = (source.borrow().now() - start).to_micros();
+        assert_eq!(elapsed_us, 2 * packet_us + 500);
+    }
+
+    #[test]
+    fn channel_from_index() {
+        assert_eq!(Channel::try_from(0), Ok(Channel::Channel1));
+        assert_eq!(Channel::try_from(1), Ok(Channel::Channel2));
+        assert_eq!(Channel::try_from(2), Ok(Channel::Channel3));
+        for index in [3, 4, 255] {
+            assert_eq!(Channel::try_from(index), Err(ChannelError::OutOfRange));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let json = serde_json::to_string(&Channel::Channel2).unwrap();
+        assert_eq!(json, r#""channel2""#);
+        assert_eq!(
+            serde_json::from_str::<Channel>(&json).unwrap(),
+            Channel::Channel2
+        );
+
+        let json = serde_json::to_string(&Command::Shock).unwrap();
+        assert_eq!(json, r#""shock""#);
+        assert_eq!(
+            serde_json::from_str::<Command>(&json).unwrap(),
+            Command::Shock
+        );
+
+        let packet = CommandPacket {
+            id: 0x0d25,
+            channel: Channel::Channel3,
+            command: Command::Vibrate,
+            strength: 50,
+        };
+        let json = serde_json::to_string(&packet).unwrap();
+        assert_eq!(
+            json,
+            r#"{"id":3365,"channel":"channel3","command":"vibrate","strength":50}"#,
+        );
+        assert_eq!(
+            serde_json::from_str::<CommandPacket>(&json).unwrap(),
+            packet
+        );
+    }
 
-        for i in (0..bits).rev() {
-            let bit_set = (val >> i) & 1 != 0;
-            let len = if bit_set { ONE_LEN } else { ZERO_LEN };
-            timings.extend([len, PULSE_LEN - len]);
+    #[test]
+    fn checksum_reference_values() {
+        let cases = [
+            (0x0d25, Channel::Channel1, Command::Shock, 50, 101),
+            (0x0d25, Channel::Channel2, Command::Vibrate, 1, 54),
+            (0x0000, Channel::Channel1, Command::Shock, 1, 2),
+            (0xffff, Channel::Channel3, Command::Beep, 99, 102),
+        ];
+        for (id, channel, command, strength, checksum) in cases {
+            assert_eq!(
+                encode_checksum(id, channel, command, strength),
+                checksum,
+                "{id:#06x} {channel:?} {command:?} {strength}",
+            );
         }
     }
 }
@@ -0,0 +1,52 @@
+//! Types for binding a [ChannelTransmitter](crate::ch8803::ChannelTransmitter)
+//! to a channel at compile time, e.g. to write functions that only accept
+//! the transmitter of a specific collar.
+
+use crate::ch8803::Channel;
+
+/// A channel known at compile time, see
+/// [typed_channel](crate::ch8803::Transmitter::typed_channel).
+pub trait FixedChannel {
+    const CHANNEL: Channel;
+}
+
+/// The channel of a ChannelTransmitter is only known at runtime.
+pub struct AnyChannel;
+
+/// A channel given by its index 0, 1 or 2. Use the aliases [Ch1], [Ch2]
+/// and [Ch3] to name them, e.g. `typed_channel::<Ch2>()` is the same as
+/// `typed_channel::<ChannelIndex<1>>()`.
+pub struct ChannelIndex<const CH: u8>;
+
+/// Channel 1.
+pub type Ch1 = ChannelIndex<0>;
+/// Channel 2.
+pub type Ch2 = ChannelIndex<1>;
+/// Channel 3.
+pub type Ch3 = ChannelIndex<2>;
+
+impl FixedChannel for Ch1 {
+    const CHANNEL: Channel = Channel::Channel1;
+}
+
+impl FixedChannel for Ch2 {
+    const CHANNEL: Channel = Channel::Channel2;
+}
+
+impl FixedChannel for Ch3 {
+    const CHANNEL: Channel = Channel::Channel3;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::any::TypeId;
+
+    #[test]
+    fn aliases_are_channel_indices() {
+        assert_eq!(TypeId::of::<Ch1>(), TypeId::of::<ChannelIndex<0>>());
+        assert_eq!(TypeId::of::<Ch2>(), TypeId::of::<ChannelIndex<1>>());
+        assert_eq!(TypeId::of::<Ch3>(), TypeId::of::<ChannelIndex<2>>());
+        assert_eq!(Ch2::CHANNEL, Channel::Channel2);
+    }
+}
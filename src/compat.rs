@@ -0,0 +1,80 @@
+//! Adapters for HALs that only implement the embedded-hal 0.2 traits, so
+//! their pins and delays can be passed to a
+//! [Transmitter](crate::ch8803::Transmitter).
+
+use core::fmt::Debug;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, ErrorKind, ErrorType, InputPin, OutputPin};
+use embedded_hal_02::blocking::delay::DelayUs;
+use embedded_hal_02::digital::v2;
+
+/// An error of a pin wrapped by [Compat02OutputPin] or [Compat02InputPin].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Compat02Error<E>(pub E);
+
+impl<E: Debug> digital::Error for Compat02Error<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Wraps an embedded-hal 0.2 output pin.
+pub struct Compat02OutputPin<P>(pub P);
+
+impl<P: v2::OutputPin> ErrorType for Compat02OutputPin<P>
+where
+    P::Error: Debug,
+{
+    type Error = Compat02Error<P::Error>;
+}
+
+impl<P: v2::OutputPin> OutputPin for Compat02OutputPin<P>
+where
+    P::Error: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(Compat02Error)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(Compat02Error)
+    }
+}
+
+/// Wraps an embedded-hal 0.2 input pin, e.g. for a
+/// [Receiver](crate::receiver::Receiver).
+pub struct Compat02InputPin<P>(pub P);
+
+impl<P: v2::InputPin> ErrorType for Compat02InputPin<P>
+where
+    P::Error: Debug,
+{
+    type Error = Compat02Error<P::Error>;
+}
+
+impl<P: v2::InputPin> InputPin for Compat02InputPin<P>
+where
+    P::Error: Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_high().map_err(Compat02Error)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_low().map_err(Compat02Error)
+    }
+}
+
+/// Wraps an embedded-hal 0.2 delay counting in microseconds. Delays in
+/// nanoseconds are rounded up to whole microseconds.
+pub struct Compat02Delay<D>(pub D);
+
+impl<D: DelayUs<u32>> DelayNs for Compat02Delay<D> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_us(ns.div_ceil(1000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+}
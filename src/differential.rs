@@ -0,0 +1,109 @@
+use crate::builder::{NeedDelay, NeedPin, TransmitterBuilder};
+use crate::ch8803::Transmitter;
+use embedded_hal::digital::{self, ErrorKind, ErrorType, OutputPin};
+
+/// A pair of pins driven in opposite directions, for RF modules with a
+/// differential DATA+/DATA− input.
+pub struct DifferentialPin<P, N> {
+    pos: P,
+    neg: N,
+}
+
+/// An error of one of the pins of a [DifferentialPin].
+#[derive(Debug)]
+pub enum DifferentialError<P, N> {
+    Pos(P),
+    Neg(N),
+}
+
+impl<P: OutputPin, N: OutputPin> DifferentialPin<P, N> {
+    pub fn new(pos: P, neg: N) -> Self {
+        Self { pos, neg }
+    }
+
+    /// Returns the positive and the negative pin.
+    pub fn into_inner(self) -> (P, N) {
+        (self.pos, self.neg)
+    }
+}
+
+impl<P: digital::Error, N: digital::Error> digital::Error
+    for DifferentialError<P, N>
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Pos(err) => err.kind(),
+            Self::Neg(err) => err.kind(),
+        }
+    }
+}
+
+impl<P: OutputPin, N: OutputPin> ErrorType for DifferentialPin<P, N> {
+    type Error = DifferentialError<P::Error, N::Error>;
+}
+
+impl<P: OutputPin, N: OutputPin> OutputPin for DifferentialPin<P, N> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pos.set_low().map_err(DifferentialError::Pos)?;
+        self.neg.set_high().map_err(DifferentialError::Neg)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pos.set_high().map_err(DifferentialError::Pos)?;
+        self.neg.set_low().map_err(DifferentialError::Neg)
+    }
+}
+
+/// A [Transmitter] that sends on a differential pair of pins. Its builder
+/// takes the two pins separately:
+///
+/// ```ignore
+/// let transmitter = DifferentialTransmitter::builder()
+///     .pin_pos(data_p)
+///     .pin_neg(data_n)
+///     .delay(&delay)
+///     .now_fn(now_fn)
+///     .id(0x1234)
+///     .build();
+/// ```
+pub type DifferentialTransmitter<'a, P, N, DELAY, NOW> =
+    Transmitter<'a, DifferentialPin<P, N>, DELAY, NOW>;
+
+/// The stage of a [TransmitterBuilder] waiting for the negative pin.
+pub struct NeedPinNeg<P>(P);
+
+type DifferentialBuilder<'a, STAGE, P, N, DELAY, NOW> =
+    TransmitterBuilder<'a, STAGE, DifferentialPin<P, N>, DELAY, NOW>;
+
+impl<'a, P: OutputPin, N: OutputPin, DELAY, NOW>
+    TransmitterBuilder<'a, NeedPin, DifferentialPin<P, N>, DELAY, NOW>
+{
+    /// The pin connected to DATA+.
+    pub fn pin_pos(
+        self,
+        pin: P,
+    ) -> TransmitterBuilder<'a, NeedPinNeg<P>, DifferentialPin<P, N>, DELAY, NOW>
+    {
+        self.stage(NeedPinNeg(pin))
+    }
+}
+
+impl<'a, P: OutputPin, N: OutputPin, DELAY, NOW>
+    TransmitterBuilder<'a, NeedPinNeg<P>, DifferentialPin<P, N>, DELAY, NOW>
+{
+    /// The pin connected to DATA−, which always has the opposite level.
+    pub fn pin_neg(
+        self,
+        pin: N,
+    ) -> DifferentialBuilder<
+        'a,
+        NeedDelay<DifferentialPin<P, N>>,
+        P,
+        N,
+        DELAY,
+        NOW,
+    > {
+        let (NeedPinNeg(pos), builder) = self.replace_stage(NeedPin);
+        builder.set_pin(DifferentialPin::new(pos, pin))
+    }
+}
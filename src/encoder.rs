@@ -0,0 +1,167 @@
+use crate::ch8803::{ONE_LEN, PULSE_LEN, ProtocolTiming, Timing, ZERO_LEN, us};
+
+const HALF_CELL: Timing = PULSE_LEN / 2;
+
+/// A scheme for turning single bits into pulse timings.
+///
+/// Timings alternate between the low and the high level, so an encoder has to
+/// emit an even number of entries per bit to keep the level of the following
+/// bits intact. Entries of zero leave the pin untouched, which allows a bit to
+/// start with the low level. At most four entries per bit are supported.
+pub trait Encoder: Copy + Default {
+    /// Writes the timings for `bit` into `timings`, starting at `idx`, and
+    /// advances `idx` past the written entries.
+    fn encode_bit(&self, bit: bool, timings: &mut [Timing], idx: &mut usize);
+
+    /// Like [encode_bit](Self::encode_bit), with the pulse lengths of a
+    /// protocol variant. Encoders with fixed timings ignore them.
+    fn encode_bit_timed(
+        &self,
+        bit: bool,
+        _timing: &ProtocolTiming,
+        timings: &mut [Timing],
+        idx: &mut usize,
+    ) {
+        self.encode_bit(bit, timings, idx);
+    }
+}
+
+/// The pulse-width encoding used by the CH8803. Every bit is a high pulse
+/// followed by a low pulse, where the high pulse is longer for a `1`.
+#[derive(Clone, Copy, Default)]
+pub struct PwmEncoder;
+
+impl Encoder for PwmEncoder {
+    fn encode_bit(&self, bit: bool, timings: &mut [Timing], idx: &mut usize) {
+        let len = if bit { ONE_LEN } else { ZERO_LEN };
+        timings[*idx] = len;
+        timings[*idx + 1] = PULSE_LEN - len;
+        *idx += 2;
+    }
+
+    fn encode_bit_timed(
+        &self,
+        bit: bool,
+        timing: &ProtocolTiming,
+        timings: &mut [Timing],
+        idx: &mut usize,
+    ) {
+        let len = if bit { timing.one_len } else { timing.zero_len };
+        timings[*idx] = us(len);
+        // A high pulse longer than the bit leaves no low pulse
+        timings[*idx + 1] = us(timing.pulse_len.saturating_sub(len));
+        *idx += 2;
+    }
+}
+
+/// Manchester encoding as in IEEE 802.3: every bit is a cell of the same
+/// length as a [PwmEncoder] bit with a transition in the middle, from high to
+/// low for a `1` and from low to high for a `0`. This is self-clocking and
+/// used by some CH8803 clones. The bit timings of a
+/// [ProtocolTiming] are ignored.
+#[derive(Clone, Copy, Default)]
+pub struct ManchesterEncoder;
+
+impl Encoder for ManchesterEncoder {
+    fn encode_bit(&self, bit: bool, timings: &mut [Timing], idx: &mut usize) {
+        if bit {
+            timings[*idx..*idx + 2].copy_from_slice(&[HALF_CELL, HALF_CELL]);
+            *idx += 2;
+        } else {
+            timings[*idx..*idx + 4]
+                .copy_from_slice(&[0, HALF_CELL, HALF_CELL, 0]);
+            *idx += 4;
+        }
+    }
+}
+
+/// The timings of a [BmcEncoder] bit cell, indexed by whether the previous
+/// cell ended high and by the bit.
+const BMC_CELLS: [[&[Timing]; 2]; 2] = [
+    // The previous cell ended low, so this one starts high
+    [&[PULSE_LEN, 0], &[HALF_CELL, HALF_CELL]],
+    // The previous cell ended high, so this one starts low
+    [&[0, PULSE_LEN], &[0, HALF_CELL, HALF_CELL, 0]],
+];
+
+/// Biphase mark coding as used by S/PDIF and some RF remotes: the level
+/// changes at the start of every bit cell, and a `1` has an additional
+/// transition in the middle. Only the transitions carry information, so the
+/// signal can be decoded regardless of its polarity. The bit timings of a
+/// [ProtocolTiming] are ignored.
+#[derive(Clone, Copy, Default)]
+pub struct BmcEncoder;
+
+impl Encoder for BmcEncoder {
+    fn encode_bit(&self, bit: bool, timings: &mut [Timing], idx: &mut usize) {
+        // Entries at odd indices are sent high, and zeros don't change the level
+        let ended_high = timings[..*idx]
+            .iter()
+            .rposition(|&duration| duration != 0)
+            .is_some_and(|last| last % 2 == 1);
+
+        let cell = BMC_CELLS[ended_high as usize][bit as usize];
+        timings[*idx..*idx + cell.len()].copy_from_slice(cell);
+        *idx += cell.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H: Timing = HALF_CELL;
+
+    /// Encodes `byte` MSB first after a low entry of zero, so the first bit
+    /// starts at an odd index like after a preamble. Returns the entries
+    /// written, without the leading zero.
+    fn encode_byte(
+        encoder: impl Encoder,
+        byte: u8,
+        timings: &mut [Timing; 33],
+    ) -> &[Timing] {
+        let mut idx = 1;
+        for i in (0..8).rev() {
+            encoder.encode_bit(byte >> i & 1 == 1, timings, &mut idx);
+        }
+        &timings[1..idx]
+    }
+
+    #[test]
+    fn manchester_and_pwm_encode_same_byte() {
+        let (one, zero) = (PULSE_LEN - ONE_LEN, PULSE_LEN - ZERO_LEN);
+        let mut timings = [0; 33];
+        let pwm = encode_byte(PwmEncoder, 0xa5, &mut timings);
+        assert_eq!(pwm.iter().sum::<Timing>(), 8 * PULSE_LEN);
+        #[rustfmt::skip]
+        assert_eq!(pwm, [
+            ONE_LEN, one, ZERO_LEN, zero, ONE_LEN, one, ZERO_LEN, zero,
+            ZERO_LEN, zero, ONE_LEN, one, ZERO_LEN, zero, ONE_LEN, one,
+        ]);
+
+        // Manchester takes as long per bit, with a transition in every cell
+        let mut timings = [0; 33];
+        let manchester = encode_byte(ManchesterEncoder, 0xa5, &mut timings);
+        assert_eq!(manchester.iter().sum::<Timing>(), 8 * PULSE_LEN);
+        #[rustfmt::skip]
+        assert_eq!(manchester, [
+            H, H, 0, H, H, 0, H, H, 0, H, H, 0,
+            0, H, H, 0, H, H, 0, H, H, 0, H, H,
+        ]);
+    }
+
+    #[test]
+    fn bmc_reference_sequence() {
+        const P: Timing = PULSE_LEN;
+        let mut timings = [0; 33];
+        let bmc = encode_byte(BmcEncoder, 0xa5, &mut timings);
+        assert_eq!(bmc.iter().sum::<Timing>(), 8 * PULSE_LEN);
+        // Starting low: 1 high-low, 0 high, 1 low-high, 0 low, 0 high,
+        // 1 low-high, 0 low, 1 high-low
+        #[rustfmt::skip]
+        assert_eq!(bmc, [
+            H, H, P, 0, 0, H, H, 0, 0, P,
+            P, 0, 0, H, H, 0, 0, P, H, H,
+        ]);
+    }
+}
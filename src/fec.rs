@@ -0,0 +1,98 @@
+/// Forward error correction applied to the payload of command packets.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Fec {
+    /// The payload is sent as is, which is what CH8803 receivers expect.
+    #[default]
+    None,
+    /// Every nibble of the payload is sent as a 7-bit Hamming(7,4)
+    /// codeword, which allows a receiver to correct a single flipped bit
+    /// per nibble.
+    Hamming74,
+}
+
+impl Fec {
+    /// Encodes a payload byte, returning the bits to send and their count.
+    pub fn encode_byte(self, byte: u8) -> (u16, u8) {
+        match self {
+            Fec::None => (byte.into(), 8),
+            Fec::Hamming74 => {
+                let high = hamming74_encode(byte >> 4);
+                let low = hamming74_encode(byte & 0xf);
+                ((u16::from(high) << 7) | u16::from(low), 14)
+            }
+        }
+    }
+}
+
+/// Encodes the nibble `d1 d2 d3 d4` (MSB first) as the codeword
+/// `p1 p2 d1 p3 d2 d3 d4`.
+pub fn hamming74_encode(nibble: u8) -> u8 {
+    let d = |i: u8| (nibble >> (3 - i)) & 1;
+    let p1 = d(0) ^ d(1) ^ d(3);
+    let p2 = d(0) ^ d(2) ^ d(3);
+    let p3 = d(1) ^ d(2) ^ d(3);
+    p1 << 6 | p2 << 5 | d(0) << 4 | p3 << 3 | d(1) << 2 | d(2) << 1 | d(3)
+}
+
+/// Decodes a codeword created by [hamming74_encode], correcting up to one
+/// flipped bit.
+pub fn hamming74_decode(codeword: u8) -> u8 {
+    // Bit of the codeword at position 1-7, from the MSB
+    let bit = |pos: u8| (codeword >> (7 - pos)) & 1;
+    let syndrome = (bit(1) ^ bit(3) ^ bit(5) ^ bit(7))
+        | (bit(2) ^ bit(3) ^ bit(6) ^ bit(7)) << 1
+        | (bit(4) ^ bit(5) ^ bit(6) ^ bit(7)) << 2;
+
+    // The syndrome is the position of the flipped bit
+    let codeword = match syndrome {
+        0 => codeword,
+        pos => codeword ^ (1 << (7 - pos)),
+    };
+    let bit = |pos: u8| (codeword >> (7 - pos)) & 1;
+    bit(3) << 3 | bit(5) << 2 | bit(6) << 1 | bit(7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_codeword() {
+        // p1 p2 d1 p3 d2 d3 d4 = 0 1 1 0 0 1 1
+        assert_eq!(hamming74_encode(0b1011), 0b011_0011);
+    }
+
+    #[test]
+    fn decodes_all_nibbles() {
+        for nibble in 0..16 {
+            let codeword = hamming74_encode(nibble);
+            assert!(codeword < 0x80, "{nibble:#x} has more than 7 bits");
+            assert_eq!(hamming74_decode(codeword), nibble);
+        }
+    }
+
+    #[test]
+    fn corrects_single_bit_flips() {
+        for nibble in 0..16 {
+            let codeword = hamming74_encode(nibble);
+            for bit in 0..7 {
+                assert_eq!(
+                    hamming74_decode(codeword ^ 1 << bit),
+                    nibble,
+                    "nibble {nibble:#x}, bit {bit}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn encode_byte_sends_both_nibbles() {
+        assert_eq!(Fec::None.encode_byte(0xb5), (0xb5, 8));
+        let (bits, count) = Fec::Hamming74.encode_byte(0xb5);
+        assert_eq!(count, 14);
+        assert_eq!(hamming74_decode((bits >> 7) as u8), 0xb);
+        assert_eq!(hamming74_decode((bits & 0x7f) as u8), 0x5);
+    }
+}
@@ -0,0 +1,84 @@
+//! A C interface to the packet encoding, for use from C, C++ and other
+//! languages. Build with the `cbindgen` feature to generate the header
+//! `include/zap_me.h`.
+
+use crate::ch8803::{Channel, Command, CommandPacket, MAX_TIMINGS};
+use core::slice;
+
+/// The call succeeded.
+pub const ZAP_OK: i32 = 0;
+/// A pointer argument was null.
+pub const ZAP_ERR_NULL: i32 = -1;
+/// The channel is not 0, 1 or 2.
+pub const ZAP_ERR_CHANNEL: i32 = -2;
+/// The command is not 1 (shock), 2 (vibrate) or 3 (beep).
+pub const ZAP_ERR_COMMAND: i32 = -3;
+/// The output buffer is too small, see [ZAP_MAX_TIMINGS].
+pub const ZAP_ERR_BUFFER: i32 = -4;
+/// The size of an output buffer that fits every packet.
+pub const ZAP_MAX_TIMINGS: usize = 300;
+const _: () = assert!(ZAP_MAX_TIMINGS == MAX_TIMINGS);
+
+/// Writes the timings of a packet into `out`, alternating between low and
+/// high and starting low. `out_len` holds the capacity of `out` on input and
+/// the number of timings written on success.
+///
+/// Returns [ZAP_OK] or one of the `ZAP_ERR_` codes, in which case nothing is
+/// written.
+///
+/// # Safety
+///
+/// `out` must be valid for writes of `*out_len` elements, and `out_len`
+/// must be valid for reads and writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zap_encode_packet(
+    id: u16,
+    channel: u8,
+    command: u8,
+    strength: u8,
+    out: *mut u32,
+    out_len: *mut usize,
+) -> i32 {
+    if out.is_null() || out_len.is_null() {
+        return ZAP_ERR_NULL;
+    }
+    let Ok(channel) = Channel::try_from(channel) else {
+        return ZAP_ERR_CHANNEL;
+    };
+    let Ok(command) = Command::try_from(command) else {
+        return ZAP_ERR_COMMAND;
+    };
+
+    let packet = CommandPacket {
+        id,
+        channel,
+        command,
+        strength,
+    };
+    let mut timings = [0; MAX_TIMINGS];
+    let len = packet.encode(&mut timings);
+
+    // SAFETY: both pointers were checked for null, and the caller guarantees
+    // that `out_len` is valid and `out` holds at least `*out_len >= len`
+    // elements
+    unsafe {
+        if *out_len < len {
+            return ZAP_ERR_BUFFER;
+        }
+        let out = slice::from_raw_parts_mut(out, len);
+        for (out, &timing) in out.iter_mut().zip(&timings[..len]) {
+            #[allow(clippy::useless_conversion)]
+            {
+                *out = u32::from(timing);
+            }
+        }
+        *out_len = len;
+    }
+    ZAP_OK
+}
+
+/// Whether `ch` is a valid channel, i.e. 0, 1 or 2.
+#[unsafe(no_mangle)]
+pub extern "C" fn zap_channel_valid(ch: u8) -> bool {
+    usize::from(ch) < Channel::COUNT
+}
@@ -0,0 +1,216 @@
+use crate::ch8803::{
+    Channel, Command, CommandPacket, Duration, InstantFn, Transmitter,
+    channel_commands,
+};
+use crate::encoder::{Encoder, PwmEncoder};
+use crate::indicator::NoPin;
+use crate::strength::StrengthMapper;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+
+/// A function applied to every command before it is sent. Returning `None`
+/// drops the command, returning a packet sends that packet instead.
+pub trait CommandFilter: Fn(&CommandPacket) -> Option<CommandPacket> {}
+impl<F: Fn(&CommandPacket) -> Option<CommandPacket>> CommandFilter for F {}
+
+/// Creates a filter limiting the strength of all commands to `max`.
+pub fn cap_strength(max: u8) -> impl CommandFilter {
+    move |packet: &CommandPacket| {
+        Some(CommandPacket {
+            strength: packet.strength.min(max),
+            ..*packet
+        })
+    }
+}
+
+/// A filter that replaces shocks by vibrations of the same strength.
+pub fn no_shock(packet: &CommandPacket) -> Option<CommandPacket> {
+    let command = match packet.command {
+        Command::Shock => Command::Vibrate,
+        command => command,
+    };
+    Some(CommandPacket { command, ..*packet })
+}
+
+/// A filter that treats the strength of all commands as perceived intensity
+/// and replaces it by the level that is felt that way, making e.g. 50 feel
+/// like half of 99. See [StrengthMapper].
+pub fn logarithmic(packet: &CommandPacket) -> Option<CommandPacket> {
+    Some(CommandPacket {
+        strength: StrengthMapper::perceptual_to_linear(packet.strength),
+        ..*packet
+    })
+}
+
+/// A [Transmitter] that passes every command through a [CommandFilter].
+pub struct FilteredTransmitter<
+    'a,
+    F,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    F: CommandFilter,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    transmitter: Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    filter: F,
+}
+
+impl<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+    Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Wraps the Transmitter so that all commands pass through `filter`.
+    pub fn filtered<F: CommandFilter>(
+        self,
+        filter: F,
+    ) -> FilteredTransmitter<'a, F, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        FilteredTransmitter {
+            transmitter: self,
+            filter,
+        }
+    }
+}
+
+impl<'a, F, PIN, DELAY, NOW, ENC, LED, BUSY>
+    FilteredTransmitter<'a, F, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    F: CommandFilter,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Adds another filter, which is applied after the existing ones.
+    pub fn filtered<G: CommandFilter>(
+        self,
+        filter: G,
+    ) -> FilteredTransmitter<
+        'a,
+        impl CommandFilter,
+        PIN,
+        DELAY,
+        NOW,
+        ENC,
+        LED,
+        BUSY,
+    > {
+        let first = self.filter;
+        FilteredTransmitter {
+            transmitter: self.transmitter,
+            filter: move |packet: &CommandPacket| {
+                first(packet).and_then(|packet| filter(&packet))
+            },
+        }
+    }
+
+    /// Returns the inner Transmitter, removing all filters.
+    pub fn into_inner(
+        self,
+    ) -> Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        self.transmitter
+    }
+
+    /// Binds the Transmitter to a specific channel, allowing to send actual
+    /// commands, see [Transmitter::channel].
+    #[allow(clippy::type_complexity)]
+    pub fn channel<'b, C: TryInto<Channel>>(
+        &'b mut self,
+        channel: C,
+    ) -> Result<
+        FilteredChannelTransmitter<'a, 'b, F, PIN, DELAY, NOW, ENC, LED, BUSY>,
+        C::Error,
+    > {
+        Ok(FilteredChannelTransmitter {
+            device: self,
+            channel: channel.try_into()?,
+        })
+    }
+
+    /// Passes the packet through the filters and sends the result, if any.
+    pub fn send_packet(
+        &mut self,
+        packet: &CommandPacket,
+        duration: impl Into<Duration>,
+    ) {
+        if let Some(packet) = (self.filter)(packet) {
+            self.transmitter.send_packet(&packet, duration);
+        }
+    }
+
+    fn send_command(
+        &mut self,
+        channel: Channel,
+        command: Command,
+        strength: u8,
+        duration: Duration,
+    ) {
+        let packet = CommandPacket {
+            id: self.transmitter.id(),
+            channel,
+            command,
+            strength,
+        };
+        self.send_packet(&packet, duration);
+    }
+}
+
+/// The filtered counterpart of [ChannelTransmitter](crate::ch8803::ChannelTransmitter).
+pub struct FilteredChannelTransmitter<
+    'a,
+    'b,
+    F,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    F: CommandFilter,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    device: &'b mut FilteredTransmitter<'a, F, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    channel: Channel,
+}
+
+impl<F, PIN, DELAY, NOW, ENC, LED, BUSY>
+    FilteredChannelTransmitter<'_, '_, F, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    F: CommandFilter,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    channel_commands!(mut);
+
+    fn send(&mut self, command: Command, strength: u8, duration: Duration) {
+        self.device
+            .send_command(self.channel, command, strength, duration);
+    }
+}
@@ -0,0 +1,444 @@
+//! A log of sent commands in NOR flash, e.g. for an audit trail that
+//! survives a power cycle.
+//!
+//! The log occupies a range of 4 KiB sectors that are used in turn. Every
+//! sector starts with a header holding the erase counter of the log, which
+//! is incremented whenever a sector is erased, so the sector with the highest
+//! counter is the one written last. Once all sectors are full, the oldest one
+//! is erased, which spreads the wear evenly across the range.
+//!
+//! Every record occupies a slot of 16 bytes: the timestamp in microseconds
+//! (`u64`), the ID (`u16`), the channel, the command and the strength, all
+//! little-endian, followed by a marker byte and two bytes of padding.
+
+use crate::ch8803::{
+    Channel, Command, CommandPacket, CommandRecord, Duration, Instant,
+};
+use embedded_storage::nor_flash::{NorFlash, NorFlashError, NorFlashErrorKind};
+
+/// The size of a sector of the log in bytes.
+pub const SECTOR_SIZE: u32 = 4096;
+const SLOT_SIZE: usize = 16;
+/// Identifies a sector header, "ZLOG" in ASCII.
+const MAGIC: u32 = 0x474F_4C5A;
+/// The value of erased flash.
+const ERASED: u8 = 0xff;
+/// The value of the marker byte of a written slot.
+const WRITTEN: u8 = 0;
+const MARKER: usize = 13;
+
+/// An error of the [FlashLog].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FlashError {
+    /// The flash reported an error.
+    Flash(NorFlashErrorKind),
+    /// The sectors do not fit the flash, are not aligned, or the flash
+    /// cannot write single slots.
+    Geometry,
+}
+
+impl<E: NorFlashError> From<E> for FlashError {
+    fn from(err: E) -> Self {
+        FlashError::Flash(err.kind())
+    }
+}
+
+/// A wear-leveling log of [CommandRecord]s with up to `N` records per
+/// sector. Only the packet and the time it was sent are stored, so the
+/// repetitions, actual duration and error of read records are zero.
+pub struct FlashLog<F: NorFlash, const N: usize> {
+    flash: F,
+    base: u32,
+    sectors: u32,
+    /// The sector records are appended to.
+    current: u32,
+    /// The next free slot in the current sector.
+    next_slot: usize,
+    /// The erase counter in the header of the current sector.
+    erase_count: u32,
+}
+
+impl<F: NorFlash, const N: usize> FlashLog<F, N> {
+    /// Opens the log in `sectors` sectors starting at the byte offset `base`
+    /// of `flash`, continuing after the newest record. If none of the
+    /// sectors holds a log yet, the first one is erased.
+    pub fn new(flash: F, base: u32, sectors: u32) -> Result<Self, FlashError> {
+        let end = u64::from(sectors) * u64::from(SECTOR_SIZE) + u64::from(base);
+        if sectors == 0
+            || !base.is_multiple_of(SECTOR_SIZE)
+            || end > flash.capacity() as u64
+            || !(SECTOR_SIZE as usize).is_multiple_of(F::ERASE_SIZE)
+            || !SLOT_SIZE.is_multiple_of(F::WRITE_SIZE)
+            || !SLOT_SIZE.is_multiple_of(F::READ_SIZE)
+            || (N + 1) * SLOT_SIZE > SECTOR_SIZE as usize
+        {
+            return Err(FlashError::Geometry);
+        }
+
+        let mut log = FlashLog {
+            flash,
+            base,
+            sectors,
+            current: 0,
+            next_slot: 0,
+            erase_count: 0,
+        };
+
+        let mut newest = None;
+        for sector in 0..sectors {
+            if let Some(count) = log.read_header(sector)?
+                && newest.is_none_or(|(_, newest)| count > newest)
+            {
+                newest = Some((sector, count));
+            }
+        }
+
+        match newest {
+            Some((sector, count)) => {
+                log.current = sector;
+                log.erase_count = count;
+                while log.next_slot < N
+                    && log.read_slot(sector, log.next_slot)?.is_some()
+                {
+                    log.next_slot += 1;
+                }
+            }
+            None => log.start_sector(0)?,
+        }
+        Ok(log)
+    }
+
+    /// Returns the flash, e.g. to use it for other data.
+    pub fn into_inner(self) -> F {
+        self.flash
+    }
+
+    /// Adds the packet and the send time of `record` to the log, erasing
+    /// the oldest sector if the current one is full.
+    pub fn append(&mut self, record: &CommandRecord) -> Result<(), FlashError> {
+        if self.next_slot == N {
+            self.start_sector((self.current + 1) % self.sectors)?;
+        }
+
+        let packet = record.packet;
+        let mut slot = [ERASED; SLOT_SIZE];
+        slot[..8].copy_from_slice(&record.sent_at.ticks().to_le_bytes());
+        slot[8..10].copy_from_slice(&packet.id.to_le_bytes());
+        slot[10] = packet.channel as u8;
+        slot[11] = packet.command as u8;
+        slot[12] = packet.strength;
+        slot[MARKER] = WRITTEN;
+
+        let offset = self.slot_offset(self.current, self.next_slot);
+        self.flash.write(offset, &slot)?;
+        self.next_slot += 1;
+        Ok(())
+    }
+
+    /// The records in the log, oldest first. Iteration ends early if
+    /// reading the flash fails, and corrupt records are skipped.
+    pub fn iter(&mut self) -> impl Iterator<Item = CommandRecord> + '_ {
+        Records {
+            log: self,
+            visited: 0,
+            slot: None,
+        }
+    }
+
+    /// Erases `sector` and makes it the current one.
+    fn start_sector(&mut self, sector: u32) -> Result<(), FlashError> {
+        let start = self.base + sector * SECTOR_SIZE;
+        self.flash.erase(start, start + SECTOR_SIZE)?;
+
+        self.erase_count += 1;
+        let mut header = [ERASED; SLOT_SIZE];
+        header[..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[4..8].copy_from_slice(&self.erase_count.to_le_bytes());
+        self.flash.write(start, &header)?;
+
+        self.current = sector;
+        self.next_slot = 0;
+        Ok(())
+    }
+
+    /// The erase counter of `sector`, or `None` if it holds no log.
+    fn read_header(&mut self, sector: u32) -> Result<Option<u32>, FlashError> {
+        let mut header = [0; SLOT_SIZE];
+        self.flash
+            .read(self.base + sector * SECTOR_SIZE, &mut header)?;
+
+        let magic = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let count = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        Ok((magic == MAGIC).then_some(count))
+    }
+
+    /// The contents of a slot, or `None` if the slot is free.
+    fn read_slot(
+        &mut self,
+        sector: u32,
+        slot: usize,
+    ) -> Result<Option<[u8; SLOT_SIZE]>, FlashError> {
+        let mut bytes = [0; SLOT_SIZE];
+        self.flash
+            .read(self.slot_offset(sector, slot), &mut bytes)?;
+        Ok((bytes[MARKER] != ERASED).then_some(bytes))
+    }
+
+    /// The offset of a slot, skipping the header in the first one.
+    fn slot_offset(&self, sector: u32, slot: usize) -> u32 {
+        self.base + sector * SECTOR_SIZE + ((slot + 1) * SLOT_SIZE) as u32
+    }
+}
+
+/// The iterator returned by [FlashLog::iter].
+struct Records<'a, F: NorFlash, const N: usize> {
+    log: &'a mut FlashLog<F, N>,
+    /// The number of sectors visited, starting with the oldest one.
+    visited: u32,
+    /// The next slot to read in the current sector, or `None` if its header
+    /// has not been checked yet.
+    slot: Option<usize>,
+}
+
+impl<F: NorFlash, const N: usize> Records<'_, F, N> {
+    fn next_sector(&mut self) {
+        self.visited += 1;
+        self.slot = None;
+    }
+}
+
+impl<F: NorFlash, const N: usize> Iterator for Records<'_, F, N> {
+    type Item = CommandRecord;
+
+    fn next(&mut self) -> Option<CommandRecord> {
+        let sectors = self.log.sectors;
+        while self.visited < sectors {
+            let sector = (self.log.current + 1 + self.visited) % sectors;
+            let Some(slot) = self.slot else {
+                // Sectors left over from an older log may have a higher
+                // counter than the current one
+                match self.log.read_header(sector) {
+                    Ok(Some(count)) if count <= self.log.erase_count => {
+                        self.slot = Some(0);
+                    }
+                    Ok(_) => self.next_sector(),
+                    Err(_) => break,
+                }
+                continue;
+            };
+            if slot == N {
+                self.next_sector();
+                continue;
+            }
+
+            match self.log.read_slot(sector, slot) {
+                Ok(Some(bytes)) => {
+                    self.slot = Some(slot + 1);
+                    if let Some(record) = decode(&bytes) {
+                        return Some(record);
+                    }
+                }
+                Ok(None) => self.next_sector(),
+                Err(_) => break,
+            }
+        }
+
+        self.visited = sectors;
+        None
+    }
+}
+
+/// Decodes the record in a written slot, or returns `None` if it is corrupt.
+fn decode(bytes: &[u8; SLOT_SIZE]) -> Option<CommandRecord> {
+    let channel = Channel::try_from(bytes[10]).ok()?;
+    let command = Command::try_from(bytes[11]).ok()?;
+    let ticks = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+
+    Some(CommandRecord {
+        packet: CommandPacket {
+            id: u16::from_le_bytes([bytes[8], bytes[9]]),
+            channel,
+            command,
+            strength: bytes[12],
+        },
+        sent_at: Instant::from_ticks(ticks),
+        repetitions: 0,
+        actual_duration: Duration::from_ticks(0),
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use embedded_storage::nor_flash::{ErrorType, ReadNorFlash};
+
+    const SECTORS: u32 = 3;
+    const CAPACITY: usize = (SECTORS * SECTOR_SIZE) as usize;
+    /// Few records per sector, so that the log wraps around quickly.
+    const PER_SECTOR: usize = 2;
+
+    type Log<'a> = FlashLog<RamFlash<'a>, PER_SECTOR>;
+
+    /// NOR flash in RAM, shared so that tests can inspect and change it
+    /// while a log is open. Writes can only clear bits, like real flash.
+    struct RamFlash<'a>(&'a RefCell<[u8; CAPACITY]>);
+
+    impl ErrorType for RamFlash<'_> {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl ReadNorFlash for RamFlash<'_> {
+        const READ_SIZE: usize = 1;
+
+        fn read(
+            &mut self,
+            offset: u32,
+            bytes: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            let start = offset as usize;
+            let data = self.0.borrow();
+            let src = data
+                .get(start..start + bytes.len())
+                .ok_or(NorFlashErrorKind::OutOfBounds)?;
+            bytes.copy_from_slice(src);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            CAPACITY
+        }
+    }
+
+    impl NorFlash for RamFlash<'_> {
+        const WRITE_SIZE: usize = 1;
+        const ERASE_SIZE: usize = SECTOR_SIZE as usize;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            let mut data = self.0.borrow_mut();
+            data.get_mut(from as usize..to as usize)
+                .ok_or(NorFlashErrorKind::OutOfBounds)?
+                .fill(ERASED);
+            Ok(())
+        }
+
+        fn write(
+            &mut self,
+            offset: u32,
+            bytes: &[u8],
+        ) -> Result<(), Self::Error> {
+            let start = offset as usize;
+            let mut data = self.0.borrow_mut();
+            let dst = data
+                .get_mut(start..start + bytes.len())
+                .ok_or(NorFlashErrorKind::OutOfBounds)?;
+            for (dst, &src) in dst.iter_mut().zip(bytes) {
+                *dst &= src;
+            }
+            Ok(())
+        }
+    }
+
+    fn erased() -> RefCell<[u8; CAPACITY]> {
+        RefCell::new([ERASED; CAPACITY])
+    }
+
+    /// A record told apart by its strength and time.
+    fn record(n: u8) -> CommandRecord {
+        CommandRecord {
+            packet: CommandPacket {
+                id: 0x0d25,
+                channel: Channel::Channel2,
+                command: Command::Vibrate,
+                strength: n,
+            },
+            sent_at: Instant::from_ticks(u64::from(n) * 1000),
+            repetitions: 0,
+            actual_duration: Duration::from_ticks(0),
+            error: None,
+        }
+    }
+
+    /// Checks that the log holds the records with the given numbers, oldest
+    /// first.
+    fn assert_records(log: &mut Log<'_>, expected: &[u8]) {
+        let mut count = 0;
+        for (read, &n) in log.iter().zip(expected) {
+            assert_eq!(read, record(n));
+            count += 1;
+        }
+        assert_eq!(count, expected.len());
+        assert_eq!(log.iter().count(), expected.len());
+    }
+
+    #[test]
+    fn reopen_after_append() {
+        let flash = erased();
+        let mut log = Log::new(RamFlash(&flash), 0, SECTORS).unwrap();
+        assert_records(&mut log, &[]);
+        for n in 1..=3 {
+            log.append(&record(n)).unwrap();
+        }
+
+        // Continues after the newest record instead of overwriting it
+        let mut log = Log::new(log.into_inner(), 0, SECTORS).unwrap();
+        assert_records(&mut log, &[1, 2, 3]);
+        log.append(&record(4)).unwrap();
+        assert_records(&mut log, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn wrap_around_erases_oldest_sector() {
+        let flash = erased();
+        let mut log = Log::new(RamFlash(&flash), 0, SECTORS).unwrap();
+        for n in 1..=6 {
+            log.append(&record(n)).unwrap();
+        }
+        assert_records(&mut log, &[1, 2, 3, 4, 5, 6]);
+
+        // All sectors are full, so the first one is erased for 7
+        log.append(&record(7)).unwrap();
+        assert_records(&mut log, &[3, 4, 5, 6, 7]);
+        log.append(&record(8)).unwrap();
+        log.append(&record(9)).unwrap();
+        assert_records(&mut log, &[5, 6, 7, 8, 9]);
+
+        // The erase counters identify the sector written last
+        let mut log = Log::new(log.into_inner(), 0, SECTORS).unwrap();
+        assert_records(&mut log, &[5, 6, 7, 8, 9]);
+        log.append(&record(10)).unwrap();
+        assert_records(&mut log, &[5, 6, 7, 8, 9, 10]);
+        log.append(&record(11)).unwrap();
+        assert_records(&mut log, &[7, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn skips_sectors_newer_than_current() {
+        let flash = erased();
+        let mut log = Log::new(RamFlash(&flash), 0, SECTORS).unwrap();
+        for n in 1..=3 {
+            log.append(&record(n)).unwrap();
+        }
+
+        // Another writer claims the last sector with a higher counter
+        let start = (2 * SECTOR_SIZE) as usize;
+        let mut data = flash.borrow_mut();
+        data[start..start + 4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[start + 4..start + 8].copy_from_slice(&99u32.to_le_bytes());
+        data[start + SLOT_SIZE + MARKER] = WRITTEN;
+        drop(data);
+
+        assert_records(&mut log, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_bad_geometry() {
+        let flash = erased();
+        let open =
+            |base, sectors| Log::new(RamFlash(&flash), base, sectors).err();
+        assert_eq!(open(0, 0), Some(FlashError::Geometry));
+        assert_eq!(open(1, 1), Some(FlashError::Geometry));
+        assert_eq!(open(0, SECTORS + 1), Some(FlashError::Geometry));
+    }
+}
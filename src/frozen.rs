@@ -0,0 +1,147 @@
+use crate::ch8803::{
+    Channel, Command, Duration, InstantFn, Transmitter, channel_commands,
+};
+use crate::encoder::{Encoder, PwmEncoder};
+use crate::indicator::NoPin;
+use crate::timing::PulseDelay;
+use core::ops::Deref;
+use embedded_hal::digital::OutputPin;
+
+/// A [Transmitter] whose settings can no longer be changed, e.g. to make sure
+/// a safety configuration stays in place. Commands can still be sent through
+/// [channel](Self::channel), and the read-only methods of the Transmitter are
+/// available through `Deref`.
+pub struct FrozenTransmitter<
+    'a,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    transmitter: Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+}
+
+impl<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+    Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Locks all settings of the Transmitter.
+    pub fn freeze(
+        self,
+    ) -> FrozenTransmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        FrozenTransmitter { transmitter: self }
+    }
+}
+
+impl<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+    FrozenTransmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Returns the inner Transmitter, allowing to change its settings again.
+    pub fn unfreeze(self) -> Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        self.transmitter
+    }
+
+    /// Binds the Transmitter to a specific channel, allowing to send actual
+    /// commands, see [Transmitter::channel]. Unlike a
+    /// [ChannelTransmitter](crate::ch8803::ChannelTransmitter), the returned
+    /// handle gives no access to the Transmitter or its calibration.
+    #[allow(clippy::type_complexity)]
+    pub fn channel<'b, C: TryInto<Channel>>(
+        &'b mut self,
+        channel: C,
+    ) -> Result<
+        FrozenChannelTransmitter<'a, 'b, PIN, DELAY, NOW, ENC, LED, BUSY>,
+        C::Error,
+    > {
+        Ok(FrozenChannelTransmitter {
+            device: &mut self.transmitter,
+            channel: channel.try_into()?,
+        })
+    }
+}
+
+/// A [FrozenTransmitter] bound to a channel. Only the basic commands are
+/// available, so the settings stay locked.
+pub struct FrozenChannelTransmitter<
+    'a,
+    'b,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    device: &'b mut Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    channel: Channel,
+}
+
+impl<PIN, DELAY, NOW, ENC, LED, BUSY>
+    FrozenChannelTransmitter<'_, '_, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    channel_commands!(mut);
+
+    /// The channel this is bound to.
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    fn send(&mut self, command: Command, strength: u8, duration: Duration) {
+        let Ok(_) =
+            self.device
+                .send_command(self.channel, command, strength, duration);
+    }
+}
+
+impl<'a, PIN, DELAY, NOW, ENC, LED, BUSY> Deref
+    for FrozenTransmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    type Target = Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.transmitter
+    }
+}
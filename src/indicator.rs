@@ -0,0 +1,37 @@
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+/// A placeholder for an indicator pin that is not connected.
+#[derive(Clone, Copy, Default)]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// An LED that is lit while a [Transmitter](crate::ch8803::Transmitter) is
+/// sending, e.g. the board LED on GPIO25 of a Raspberry Pi Pico. Add it with
+/// [with_led](crate::ch8803::Transmitter::with_led).
+pub struct LedIndicator<L: OutputPin>(pub L);
+
+impl<L: OutputPin> LedIndicator<L> {
+    /// Turns the LED on.
+    pub fn on(&mut self) {
+        let _ = self.0.set_high();
+    }
+
+    /// Turns the LED off.
+    pub fn off(&mut self) {
+        let _ = self.0.set_low();
+    }
+}
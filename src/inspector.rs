@@ -0,0 +1,182 @@
+use crate::ch8803::{
+    Channel, Command, CommandPacket, Duration, InstantFn, Timing, Transmitter,
+    channel_commands,
+};
+use crate::encoder::{Encoder, PwmEncoder};
+use crate::indicator::NoPin;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+
+/// The point in the transmission of a packet at which a [PacketInspector] is
+/// called.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// Right before a repetition of the packet is sent.
+    BeforeTransmit,
+    /// Right after a repetition of the packet was sent.
+    AfterTransmit,
+}
+
+/// A function called with the raw pulse timings of every repetition of a
+/// packet, before and after it is sent. The timings alternate between the
+/// low and the high level, starting with low.
+pub trait PacketInspector: for<'a> Fn(Phase, &'a [Timing]) {}
+impl<F: for<'a> Fn(Phase, &'a [Timing])> PacketInspector for F {}
+
+pub(crate) fn no_inspection(_: Phase, _: &[Timing]) {}
+
+/// A [Transmitter] that passes the timings of every packet to a
+/// [PacketInspector].
+pub struct InspectedTransmitter<
+    'a,
+    I,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    I: PacketInspector,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    transmitter: Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    inspector: I,
+}
+
+impl<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+    Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Wraps the Transmitter so that the timings of all commands are passed
+    /// to `inspector`.
+    pub fn inspected<I: PacketInspector>(
+        self,
+        inspector: I,
+    ) -> InspectedTransmitter<'a, I, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        InspectedTransmitter {
+            transmitter: self,
+            inspector,
+        }
+    }
+}
+
+impl<'a, I, PIN, DELAY, NOW, ENC, LED, BUSY>
+    InspectedTransmitter<'a, I, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    I: PacketInspector,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    /// Returns the inner Transmitter, removing the inspector.
+    pub fn into_inner(
+        self,
+    ) -> Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        self.transmitter
+    }
+
+    /// Binds the Transmitter to a specific channel, allowing to send actual
+    /// commands, see [Transmitter::channel].
+    #[allow(clippy::type_complexity)]
+    pub fn channel<'b, C: TryInto<Channel>>(
+        &'b mut self,
+        channel: C,
+    ) -> Result<
+        InspectedChannelTransmitter<'a, 'b, I, PIN, DELAY, NOW, ENC, LED, BUSY>,
+        C::Error,
+    > {
+        Ok(InspectedChannelTransmitter {
+            device: self,
+            channel: channel.try_into()?,
+        })
+    }
+
+    /// Sends a packet to the receiver for the given duration, passing the
+    /// timings of every repetition to the inspector.
+    pub fn send_packet(
+        &mut self,
+        packet: &CommandPacket,
+        duration: impl Into<Duration>,
+    ) {
+        self.transmitter.send_packet_inspected(
+            packet,
+            duration.into(),
+            &self.inspector,
+        );
+    }
+
+    fn send_command(
+        &mut self,
+        channel: Channel,
+        command: Command,
+        strength: u8,
+        duration: Duration,
+    ) {
+        let packet = CommandPacket {
+            id: self.transmitter.id(),
+            channel,
+            command,
+            strength,
+        };
+        self.send_packet(&packet, duration);
+    }
+}
+
+/// The inspected counterpart of [ChannelTransmitter](crate::ch8803::ChannelTransmitter).
+pub struct InspectedChannelTransmitter<
+    'a,
+    'b,
+    I,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    I: PacketInspector,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    device:
+        &'b mut InspectedTransmitter<'a, I, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    channel: Channel,
+}
+
+impl<I, PIN, DELAY, NOW, ENC, LED, BUSY>
+    InspectedChannelTransmitter<'_, '_, I, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    I: PacketInspector,
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    channel_commands!(mut);
+
+    fn send(&mut self, command: Command, strength: u8, duration: Duration) {
+        self.device
+            .send_command(self.channel, command, strength, duration);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::ch8803::Transmitter;
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal::pwm::SetDutyCycle;
+
+/// Output for infrared LEDs: `pwm` generates the carrier, and `pin` gates
+/// it, e.g. through an AND gate or a transistor in series with the LED.
+///
+/// The PWM has to be configured for the carrier frequency of the receiver,
+/// usually 38 kHz. It runs with a duty cycle of 50% for as long as the IrPin
+/// exists.
+pub struct IrPin<PIN, PWM> {
+    pin: PIN,
+    pwm: PWM,
+}
+
+/// An error while setting up an [IrPin].
+#[derive(Debug)]
+pub enum IrError<P, W> {
+    Pin(P),
+    Pwm(W),
+}
+
+impl<PIN: OutputPin, PWM: SetDutyCycle> IrPin<PIN, PWM> {
+    /// Starts the carrier and turns the gate off.
+    pub fn new(
+        mut pin: PIN,
+        mut pwm: PWM,
+    ) -> Result<Self, IrError<PIN::Error, PWM::Error>> {
+        pin.set_low().map_err(IrError::Pin)?;
+        pwm.set_duty_cycle_percent(50).map_err(IrError::Pwm)?;
+        Ok(Self { pin, pwm })
+    }
+
+    /// Stops the carrier and returns the gate pin and the PWM.
+    pub fn into_inner(mut self) -> (PIN, PWM) {
+        let _ = self.pwm.set_duty_cycle_fully_off();
+        (self.pin, self.pwm)
+    }
+}
+
+impl<PIN: OutputPin, PWM> ErrorType for IrPin<PIN, PWM> {
+    type Error = PIN::Error;
+}
+
+impl<PIN: OutputPin, PWM> OutputPin for IrPin<PIN, PWM> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+}
+
+/// A [Transmitter] sending the packets as modulated infrared light instead of
+/// 433 MHz radio. Build it with an [IrPin] as `pin`.
+pub type IrTransmitter<'a, PIN, PWM, DELAY, NOW> =
+    Transmitter<'a, IrPin<PIN, PWM>, DELAY, NOW>;
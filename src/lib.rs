@@ -1,3 +1,61 @@
 #![no_std]
+// Only the C interface needs unsafe code, which has to pass Miri, see
+// miri-setup.sh
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![deny(clippy::undocumented_unsafe_blocks)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "async")]
+pub mod asynch;
+#[cfg(feature = "ble")]
+pub mod ble_bridge;
+pub mod builder;
+pub mod capture;
+pub mod cbuf;
 pub mod ch8803;
+pub mod channels;
+#[cfg(feature = "0.2-compat")]
+pub mod compat;
+pub mod differential;
+pub mod encoder;
+pub mod fec;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+#[cfg(feature = "embedded-storage")]
+pub mod flash_log;
+pub mod frozen;
+pub mod indicator;
+pub mod inspector;
+pub mod ir;
+#[cfg(feature = "linux")]
+pub mod linux;
+pub mod model;
+#[cfg(feature = "mqtt")]
+pub mod mqtt_bridge;
+pub mod nec;
+#[cfg(any(feature = "alloc", feature = "rp2040"))]
+pub mod pin;
+pub mod pulse;
+pub mod receiver;
+#[cfg(feature = "diagnostics")]
+pub mod recorder;
+#[cfg(feature = "rp2040")]
+pub mod rp2040;
+pub mod sequence;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "spi")]
+pub mod spi;
+pub mod strength;
+pub mod timing;
+#[cfg(feature = "usb")]
+pub mod usb_commander;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod waveform;
+pub mod whitening;
@@ -0,0 +1,63 @@
+use crate::ch8803::Instant;
+use core::convert::Infallible;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorType, OutputPin};
+use std::time;
+
+/// A GPIO of a Raspberry Pi used as output pin.
+pub struct RppalPin(pub rppal::gpio::OutputPin);
+
+impl RppalPin {
+    /// Configures the pin with the given BCM number as output.
+    pub fn new(bcm_pin: u8) -> Result<Self, rppal::gpio::Error> {
+        let pin = rppal::gpio::Gpio::new()?.get(bcm_pin)?;
+        Ok(Self(pin.into_output_low()))
+    }
+}
+
+impl ErrorType for RppalPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for RppalPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.set_low();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.set_high();
+        Ok(())
+    }
+}
+
+/// A delay for Linux hosts. It spins instead of calling
+/// `std::thread::sleep`, as the wake-up latency of the scheduler is in the
+/// order of the pulse lengths.
+#[derive(Clone, Copy, Default)]
+pub struct RppalDelay;
+
+impl RppalDelay {
+    fn spin(duration: time::Duration) {
+        let end = time::Instant::now() + duration;
+        while time::Instant::now() < end {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl DelayNs for RppalDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        Self::spin(time::Duration::from_nanos(ns.into()));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        Self::spin(time::Duration::from_micros(us.into()));
+    }
+}
+
+/// Creates a `now_fn` counting the time since it was created.
+pub fn now_fn() -> impl Fn() -> Instant {
+    let start = time::Instant::now();
+    move || Instant::from_ticks(start.elapsed().as_micros() as u64)
+}
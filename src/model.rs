@@ -0,0 +1,65 @@
+//! Presets of the protocol settings of a
+//! [Transmitter](crate::ch8803::Transmitter) for specific receivers, see
+//! [for_model](crate::ch8803::Transmitter::for_model).
+
+use crate::ch8803::{
+    DEFAULT_FOOTER, DEFAULT_PREAMBLE, MAX_FOOTER, MAX_PREAMBLE, ProtocolTiming,
+    TimingSequence,
+};
+use crate::fec::Fec;
+use crate::whitening::DataWhitening;
+
+/// The protocol settings a [CollarModel] selects. The bit order and checksum
+/// are those of the CH8803 packet layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProtocolConfig {
+    /// The preamble sent before every packet, see
+    /// [set_preamble](crate::ch8803::Transmitter::set_preamble).
+    pub preamble: TimingSequence<MAX_PREAMBLE>,
+
+    /// The footer sent after every packet, see
+    /// [set_footer](crate::ch8803::Transmitter::set_footer).
+    pub footer: TimingSequence<MAX_FOOTER>,
+
+    /// The pulse lengths of the bits. The preamble and footer of the
+    /// config take precedence over the ones derived from them.
+    pub timing: ProtocolTiming,
+
+    /// The whitening applied to the payload, if any.
+    pub whitening: Option<DataWhitening>,
+
+    /// The error correction applied to the payload.
+    pub fec: Fec,
+}
+
+impl Default for ProtocolConfig {
+    /// The settings of the original CH8803 remote.
+    fn default() -> Self {
+        Self {
+            preamble: DEFAULT_PREAMBLE,
+            footer: DEFAULT_FOOTER,
+            timing: ProtocolTiming::ch8803_default(),
+            whitening: None,
+            fec: Fec::None,
+        }
+    }
+}
+
+/// A receiver whose protocol settings are known.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollarModel {
+    /// Receivers of the original CH8803 remote.
+    Ch8803,
+    /// A clone or modified receiver with its own settings.
+    Custom(ProtocolConfig),
+}
+
+impl CollarModel {
+    /// The protocol settings of the model.
+    pub fn config(self) -> ProtocolConfig {
+        match self {
+            CollarModel::Ch8803 => ProtocolConfig::default(),
+            CollarModel::Custom(config) => config,
+        }
+    }
+}
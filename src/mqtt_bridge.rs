@@ -0,0 +1,275 @@
+//! Forwarding commands received over MQTT, e.g. from a home automation
+//! system, to a [Transmitter] on an ESP32 or any other device with a
+//! network connection.
+//!
+//! The bridge is independent of the MQTT client. Subscribe to
+//! [TOPIC_FILTER] and pass every message to [MqttBridge::handle_message];
+//! `examples/esp32_zap` does this with the client of `esp-idf-svc`, and
+//! `examples/esp32_zap/mosquitto` contains a broker for local testing.
+//!
+//! # Topics
+//!
+//! Commands are published to `collar/<id>/command`, where `<id>` is the
+//! decimal ID the receiver is paired with, e.g. `collar/3365/command` for
+//! ID `0x0D25`. One bridge serves all IDs, regardless of the ID of its
+//! Transmitter.
+//!
+//! # Payload
+//!
+//! Payloads starting with `{` are JSON objects with the fields of
+//! [MqttCommand]:
+//!
+//! ```json
+//! {"channel": 1, "command": "vibrate", "strength": 50, "duration_ms": 500}
+//! ```
+//!
+//! Unlike the C, WebAssembly and BLE interfaces, which take the channel
+//! index 0–2 like `Channel::try_from`, `channel` is numbered **1–3** here,
+//! as on the original remote, see [Channel::from_number]. `command` is one
+//! of `shock`, `vibrate` and `beep`, and `strength` may be left out for
+//! beeps. All other payloads are decoded as `postcard`
+//! serialization of [MqttCommand], where the commands are the variant
+//! indices 0, 1 and 2 and the numbers are varints.
+
+use crate::ch8803::{
+    Channel, Command, CommandPacket, Duration, InstantFn, Transmitter,
+};
+use crate::encoder::Encoder;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+use serde::{Deserialize, Serialize};
+
+/// The topic filter to subscribe to.
+pub const TOPIC_FILTER: &str = "collar/+/command";
+
+/// The command kinds of an [MqttCommand].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MqttAction {
+    Shock,
+    Vibrate,
+    Beep,
+}
+
+/// The payload of a command message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct MqttCommand {
+    /// The channel, 1–3 as on the original remote.
+    pub channel: u8,
+    pub command: MqttAction,
+    /// The strength, ignored for beeps.
+    #[serde(default)]
+    pub strength: u8,
+    pub duration_ms: u32,
+}
+
+/// The reason a message was rejected. Nothing is sent for rejected messages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MqttError {
+    /// The topic does not match `collar/<id>/command`.
+    Topic,
+    /// The payload is neither a valid JSON object nor postcard data.
+    Payload,
+    /// The channel is not 1, 2 or 3.
+    Channel,
+}
+
+/// Dispatches messages of the [TOPIC_FILTER] topics.
+#[derive(Clone, Copy, Default)]
+pub struct MqttBridge {
+    max_duration_ms: Option<u32>,
+}
+
+impl MqttBridge {
+    pub const fn new() -> Self {
+        Self {
+            max_duration_ms: None,
+        }
+    }
+
+    /// Limits the duration of every command, regardless of what the message
+    /// requests.
+    pub const fn with_max_duration(max_duration_ms: u32) -> Self {
+        Self {
+            max_duration_ms: Some(max_duration_ms),
+        }
+    }
+
+    /// Returns the ID in a topic of the form `collar/<id>/command`.
+    pub fn parse_topic(topic: &str) -> Result<u16, MqttError> {
+        let mut levels = topic.split('/');
+        let (Some("collar"), Some(id), Some("command"), None) =
+            (levels.next(), levels.next(), levels.next(), levels.next())
+        else {
+            return Err(MqttError::Topic);
+        };
+        id.parse().map_err(|_| MqttError::Topic)
+    }
+
+    /// Decodes a JSON or postcard payload.
+    pub fn decode_payload(payload: &[u8]) -> Result<MqttCommand, MqttError> {
+        if payload.first() == Some(&b'{') {
+            serde_json_core::from_slice(payload)
+                .map(|(command, _)| command)
+                .map_err(|_| MqttError::Payload)
+        } else {
+            postcard::from_bytes(payload).map_err(|_| MqttError::Payload)
+        }
+    }
+
+    /// Sends the command of a message and returns the packet that was sent.
+    pub fn handle_message<PIN, DELAY, NOW, ENC, LED, BUSY>(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        tx: &mut Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) -> Result<CommandPacket, MqttError>
+    where
+        PIN: OutputPin,
+        DELAY: PulseDelay,
+        NOW: InstantFn,
+        ENC: Encoder,
+        LED: OutputPin,
+        BUSY: OutputPin,
+    {
+        let id = Self::parse_topic(topic)?;
+        let message = Self::decode_payload(payload)?;
+        let channel = Channel::from_number(message.channel)
+            .map_err(|_| MqttError::Channel)?;
+        let (command, strength) = match message.command {
+            MqttAction::Shock => (Command::Shock, message.strength),
+            MqttAction::Vibrate => (Command::Vibrate, message.strength),
+            MqttAction::Beep => (Command::Beep, 0),
+        };
+        let packet = CommandPacket {
+            id,
+            channel,
+            command,
+            strength,
+        };
+
+        let duration_ms = match self.max_duration_ms {
+            Some(max) => message.duration_ms.min(max),
+            None => message.duration_ms,
+        };
+        tx.send_packet(&packet, Duration::millis(duration_ms));
+        Ok(packet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::NoPin;
+    use crate::timing::{self, MockTimingSource};
+    use core::cell::RefCell;
+
+    #[test]
+    fn parses_topics() {
+        assert_eq!(MqttBridge::parse_topic("collar/3365/command"), Ok(3365));
+        assert_eq!(MqttBridge::parse_topic("collar/0/command"), Ok(0));
+        for topic in [
+            "collar/3365",
+            "collar/3365/command/",
+            "collar/3365/state",
+            "collars/3365/command",
+            "collar//command",
+            "collar/0x0d25/command",
+            "collar/65536/command",
+        ] {
+            assert_eq!(
+                MqttBridge::parse_topic(topic),
+                Err(MqttError::Topic),
+                "{topic}"
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_json() {
+        let payload = br#"{"channel": 2, "command": "vibrate", "strength": 50, "duration_ms": 500}"#;
+        assert_eq!(
+            MqttBridge::decode_payload(payload),
+            Ok(MqttCommand {
+                channel: 2,
+                command: MqttAction::Vibrate,
+                strength: 50,
+                duration_ms: 500,
+            })
+        );
+
+        // The strength may be left out, e.g. for beeps
+        let payload =
+            br#"{"channel": 1, "command": "beep", "duration_ms": 250}"#;
+        assert_eq!(
+            MqttBridge::decode_payload(payload).map(|c| c.strength),
+            Ok(0)
+        );
+
+        let payload =
+            br#"{"channel": 1, "command": "zap", "duration_ms": 250}"#;
+        assert_eq!(
+            MqttBridge::decode_payload(payload),
+            Err(MqttError::Payload)
+        );
+    }
+
+    #[test]
+    fn decodes_postcard() {
+        // Channel 3, beep (variant 2), strength 0, 300 ms as varint
+        let payload = [3, 2, 0, 0xac, 0x02];
+        assert_eq!(
+            MqttBridge::decode_payload(&payload),
+            Ok(MqttCommand {
+                channel: 3,
+                command: MqttAction::Beep,
+                strength: 0,
+                duration_ms: 300,
+            })
+        );
+        assert_eq!(
+            MqttBridge::decode_payload(&payload[..3]),
+            Err(MqttError::Payload)
+        );
+    }
+
+    #[test]
+    fn handles_messages() {
+        let source = RefCell::new(MockTimingSource::default());
+        let mut tx = timing::transmitter(NoPin, &source, 1);
+        let bridge = MqttBridge::new();
+
+        let payload = br#"{"channel": 3, "command": "shock", "strength": 20, "duration_ms": 100}"#;
+        assert_eq!(
+            bridge.handle_message("collar/3365/command", payload, &mut tx),
+            Ok(CommandPacket {
+                id: 3365,
+                channel: Channel::Channel3,
+                command: Command::Shock,
+                strength: 20,
+            })
+        );
+
+        // Beeps are sent without strength, even if one is given
+        let payload = br#"{"channel": 1, "command": "beep", "strength": 20, "duration_ms": 100}"#;
+        assert_eq!(
+            bridge
+                .handle_message("collar/3365/command", payload, &mut tx)
+                .map(|packet| packet.strength),
+            Ok(0)
+        );
+
+        for channel in [0, 4] {
+            let payload = [channel, 0, 50, 100];
+            assert_eq!(
+                bridge.handle_message("collar/3365/command", &payload, &mut tx),
+                Err(MqttError::Channel)
+            );
+        }
+        assert_eq!(
+            bridge.handle_message("collar/3365", payload, &mut tx),
+            Err(MqttError::Topic)
+        );
+    }
+}
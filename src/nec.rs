@@ -0,0 +1,93 @@
+use crate::ch8803::{InstantFn, Timing, TransmitError, Transmitter, us};
+use crate::encoder::Encoder;
+use crate::indicator::NoPin;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+
+/// The length of a mark and of the space of a `0`, 562.5 µs rounded.
+const BIT_LEN: Timing = us(562);
+/// The space of a `1`.
+const ONE_SPACE: Timing = us(1687);
+/// The leading 9 ms mark and 4.5 ms space, after an empty low entry.
+const LEADER: [Timing; 3] = [0, us(9000), us(4500)];
+const TIMINGS: usize = LEADER.len() + 32 * 2 + 1;
+
+/// A command for an NEC receiver.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NecPacket {
+    pub address: u8,
+    pub command: u8,
+}
+
+/// The pulse-distance encoding of NEC: every bit is a mark of 562.5 µs followed
+/// by a space that is three times as long for a `1`.
+#[derive(Clone, Copy, Default)]
+pub struct NecEncoder;
+
+impl Encoder for NecEncoder {
+    fn encode_bit(&self, bit: bool, timings: &mut [Timing], idx: &mut usize) {
+        timings[*idx] = BIT_LEN;
+        timings[*idx + 1] = if bit { ONE_SPACE } else { BIT_LEN };
+        *idx += 2;
+    }
+}
+
+/// Sends [NecPacket]s of the NEC infrared protocol used by many consumer
+/// remotes, with the pin, delay and indicators of a [Transmitter]. Use it
+/// with an [IrPin](crate::ir::IrPin) and a 38 kHz carrier.
+pub struct NecTransmitter<'a, PIN, DELAY, NOW, LED = NoPin, BUSY = NoPin>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    transmitter: Transmitter<'a, PIN, DELAY, NOW, NecEncoder, LED, BUSY>,
+}
+
+impl<'a, PIN, DELAY, NOW, LED, BUSY>
+    NecTransmitter<'a, PIN, DELAY, NOW, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    pub fn new<ENC: Encoder>(
+        transmitter: Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) -> Self {
+        Self {
+            transmitter: transmitter.with_encoder(NecEncoder),
+        }
+    }
+
+    /// Sends the packet once. The address and command are followed by their
+    /// inverse, all LSB first.
+    pub fn send(&mut self, packet: NecPacket) -> Result<(), TransmitError> {
+        let mut timings = [0; TIMINGS];
+        timings[..LEADER.len()].copy_from_slice(&LEADER);
+        let mut idx = LEADER.len();
+
+        let bytes = [
+            packet.address,
+            !packet.address,
+            packet.command,
+            !packet.command,
+        ];
+        for byte in bytes {
+            for i in 0..8 {
+                NecEncoder.encode_bit(
+                    byte >> i & 1 != 0,
+                    &mut timings,
+                    &mut idx,
+                );
+            }
+        }
+        // The final mark ends the space of the last bit
+        timings[idx] = BIT_LEN;
+
+        self.transmitter.send_raw(&timings)
+    }
+}
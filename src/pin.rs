@@ -0,0 +1,53 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use embedded_hal::digital::{Error, ErrorType, OutputPin};
+
+/// An output pin with its concrete type erased, for pins that are only known
+/// at runtime (e.g. when the GPIO number is read from a config).
+#[cfg(feature = "alloc")]
+pub struct ErasedPin<E>(pub Box<dyn OutputPin<Error = E>>);
+
+#[cfg(feature = "alloc")]
+impl<E: Error> ErasedPin<E> {
+    /// Erases the type of the given pin.
+    pub fn new(pin: impl OutputPin<Error = E> + 'static) -> Self {
+        Self(Box::new(pin))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Error> ErrorType for ErasedPin<E> {
+    type Error = E;
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Error> OutputPin for ErasedPin<E> {
+    fn set_low(&mut self) -> Result<(), E> {
+        self.0.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), E> {
+        self.0.set_high()
+    }
+}
+
+/// The type of an RP2040 pin returned by [into_erased_pin].
+#[cfg(feature = "rp2040")]
+pub type Rp2040ErasedPin = rp2040_hal::gpio::Pin<
+    rp2040_hal::gpio::DynPinId,
+    rp2040_hal::gpio::FunctionSioOutput,
+    rp2040_hal::gpio::DynPullType,
+>;
+
+/// Configures an RP2040 pin as push-pull output and erases its pin number.
+/// Unlike [ErasedPin], this does not need an allocator.
+#[cfg(feature = "rp2040")]
+pub fn into_erased_pin<P>(pin: P) -> Rp2040ErasedPin
+where
+    P: rp2040_hal::gpio::AnyPin,
+    P::Id: rp2040_hal::gpio::ValidFunction<rp2040_hal::gpio::FunctionSioOutput>,
+{
+    let pin: rp2040_hal::gpio::SpecificPin<P> = pin.into();
+    pin.into_push_pull_output().into_pull_type().into_dyn_pin()
+}
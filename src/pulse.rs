@@ -0,0 +1,77 @@
+use crate::ch8803::{Timing, us};
+
+/// A waveform assembled pulse by pulse, for protocols the crate does not
+/// know yet. Send it with
+/// [send_pulse_train](crate::ch8803::Transmitter::send_pulse_train).
+///
+/// Consecutive pulses of the same level are merged into one, so the train
+/// always alternates between low and high. It holds `N` pulses, or `N - 1`
+/// if it starts high.
+pub struct PulseTrain<const N: usize> {
+    timings: [Timing; N],
+    len: usize,
+}
+
+impl<const N: usize> PulseTrain<N> {
+    pub const fn new() -> Self {
+        Self {
+            timings: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Appends a high pulse, returning `false` if the train is full.
+    pub fn push_high(&mut self, micros: u16) -> bool {
+        self.push(true, micros)
+    }
+
+    /// Appends a low pulse, returning `false` if the train is full.
+    pub fn push_low(&mut self, micros: u16) -> bool {
+        self.push(false, micros)
+    }
+
+    /// The timings in the format sent by the
+    /// [Transmitter](crate::ch8803::Transmitter): entries alternate between
+    /// low and high, starting low, and zeros are skipped.
+    pub fn as_slice(&self) -> &[Timing] {
+        &self.timings[..self.len]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all pulses.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn push(&mut self, high: bool, micros: u16) -> bool {
+        let timing = us(micros);
+        // Entries at odd indices are sent high
+        let last_high = self.len.is_multiple_of(2);
+        if self.len > 0 && last_high == high {
+            let last = &mut self.timings[self.len - 1];
+            *last = last.saturating_add(timing);
+            return true;
+        }
+
+        let skip = usize::from(self.len == 0 && high);
+        if self.len + skip >= N {
+            return false;
+        }
+        if skip == 1 {
+            self.timings[0] = 0;
+        }
+        self.len += skip;
+        self.timings[self.len] = timing;
+        self.len += 1;
+        true
+    }
+}
+
+impl<const N: usize> Default for PulseTrain<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,105 @@
+use crate::capture::PacketDecoder;
+use crate::ch8803::{Channel, Command, CommandPacket, Instant, InstantFn};
+use embedded_hal::digital::InputPin;
+
+/// A low pulse longer than this ends a packet that is being received. The
+/// longest low pulse within a packet is the 840 µs of the preamble.
+const MAX_GAP_US: u64 = 2000;
+
+/// A packet decoded by a [Receiver], whose checksum may be wrong.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReceivedPacket {
+    pub id: u16,
+    pub channel: Channel,
+    pub command: Command,
+    pub strength: u8,
+    /// Whether the checksum matches the other fields. A mismatch hints at
+    /// interference or a receiver with a different checksum algorithm.
+    pub checksum_valid: bool,
+}
+
+impl ReceivedPacket {
+    /// Decodes the 42 bits of a packet as sent over the air, MSB first.
+    /// Returns `None` if the channel or command is out of range.
+    fn from_bits(bits: u64) -> Option<Self> {
+        let (packet, checksum_valid) =
+            CommandPacket::from_bits_unchecked(bits)?;
+        Some(Self {
+            id: packet.id,
+            channel: packet.channel,
+            command: packet.command,
+            strength: packet.strength,
+            checksum_valid,
+        })
+    }
+
+    /// The packet, if its checksum is valid.
+    pub fn packet(&self) -> Option<CommandPacket> {
+        self.checksum_valid.then_some(CommandPacket {
+            id: self.id,
+            channel: self.channel,
+            command: self.command,
+            strength: self.strength,
+        })
+    }
+}
+
+/// Receives CH8803 packets by polling a pin connected to a 433 MHz receiver
+/// module, e.g. to find out the ID of an original remote. Like
+/// [PacketDecoder], only packets sent with the
+/// [PwmEncoder](crate::encoder::PwmEncoder) and without whitening or error
+/// correction are recognized.
+pub struct Receiver<IP: InputPin, NOW: InstantFn> {
+    pin: IP,
+    now_fn: NOW,
+    level: bool,
+    last_edge: Instant,
+    decoder: PacketDecoder,
+}
+
+impl<IP: InputPin, NOW: InstantFn> Receiver<IP, NOW> {
+    /// Creates a receiver for the pin, taking timestamps from `now_fn`.
+    pub fn new(pin: IP, now_fn: NOW) -> Self {
+        let last_edge = now_fn();
+        Self {
+            pin,
+            now_fn,
+            level: false,
+            last_edge,
+            decoder: PacketDecoder::new(),
+        }
+    }
+
+    /// Returns the pin.
+    pub fn into_inner(self) -> IP {
+        self.pin
+    }
+
+    /// Samples the pin once and returns the packet completed by an edge,
+    /// including packets with a wrong checksum. Has to be called at least
+    /// every few microseconds to measure the pulses accurately.
+    pub fn poll(&mut self) -> Result<Option<ReceivedPacket>, IP::Error> {
+        let high = self.pin.is_high()?;
+        if high == self.level {
+            return Ok(None);
+        }
+
+        let now = (self.now_fn)();
+        let us = (now - self.last_edge).to_micros();
+        self.last_edge = now;
+        self.level = high;
+
+        if high {
+            // Bits before the next preamble, e.g. when polling starts in
+            // the middle of a packet, are ignored
+            if us > MAX_GAP_US {
+                self.decoder = PacketDecoder::new();
+            }
+            return Ok(None);
+        }
+        Ok(self
+            .decoder
+            .high_pulse_bits(us)
+            .and_then(ReceivedPacket::from_bits))
+    }
+}
@@ -0,0 +1,143 @@
+use crate::ch8803::{
+    CommandPacket, Instant, InstantFn, MAX_TIMINGS, TIMING_PER_US, Timing,
+    TransmitError, Transmitter,
+};
+use crate::encoder::{Encoder, PwmEncoder};
+use crate::indicator::NoPin;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+
+/// The deviation up to which [WaveformRecorder::quality_score] considers a
+/// pin operation on time.
+const TOLERANCE_US: i32 = 10;
+
+/// A [Transmitter] that records when it actually sets the pin, to measure the
+/// timing accuracy of the hardware without a logic analyzer. At most `N`
+/// pin operations of the last transmission are recorded.
+pub struct WaveformRecorder<
+    'a,
+    const N: usize,
+    PIN,
+    DELAY,
+    NOW,
+    ENC = PwmEncoder,
+    LED = NoPin,
+    BUSY = NoPin,
+> where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    transmitter: Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    /// The expected offset from the first operation in microseconds and the
+    /// time the operation actually happened.
+    edges: [(u64, Instant); N],
+    len: usize,
+}
+
+impl<'a, const N: usize, PIN, DELAY, NOW, ENC, LED, BUSY>
+    WaveformRecorder<'a, N, PIN, DELAY, NOW, ENC, LED, BUSY>
+where
+    PIN: OutputPin,
+    DELAY: PulseDelay,
+    NOW: InstantFn,
+    ENC: Encoder,
+    LED: OutputPin,
+    BUSY: OutputPin,
+{
+    pub fn new(
+        transmitter: Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) -> Self {
+        Self {
+            transmitter,
+            edges: [(0, Instant::from_ticks(0)); N],
+            len: 0,
+        }
+    }
+
+    /// Returns the inner Transmitter.
+    pub fn into_inner(
+        self,
+    ) -> Transmitter<'a, PIN, DELAY, NOW, ENC, LED, BUSY> {
+        self.transmitter
+    }
+
+    /// Sends a single repetition of `packet` and records it.
+    pub fn record_packet(
+        &mut self,
+        packet: &CommandPacket,
+    ) -> Result<(), TransmitError> {
+        let mut timings = [0; MAX_TIMINGS];
+        let len = self.transmitter.encode_into(packet, &mut timings);
+        self.record_timings(&timings[..len])
+    }
+
+    /// Sends `timings` once and records them, replacing the previous record.
+    pub fn record_timings(
+        &mut self,
+        timings: &[Timing],
+    ) -> Result<(), TransmitError> {
+        self.len = 0;
+        let edges = &mut self.edges;
+        let len = &mut self.len;
+        // Sum up incrementally to keep the work between pin operations short
+        let (mut expected, mut summed) = (0u64, 0);
+        self.transmitter.send_raw_with(timings, |i, now_fn: &NOW| {
+            expected += timings[summed..i]
+                .iter()
+                .map(|&t| u64::from(t))
+                .sum::<u64>();
+            summed = i;
+            if *len < N {
+                edges[*len] = (expected / TIMING_PER_US, now_fn());
+                *len += 1;
+            }
+        })
+    }
+
+    /// The deviation of the recorded pin operations from their expected time
+    /// relative to the first one, as minimum, maximum and mean in
+    /// microseconds. Positive values are late. All zero if nothing was
+    /// recorded.
+    pub fn jitter_stats(&self) -> (i32, i32, i32) {
+        if self.len == 0 {
+            return (0, 0, 0);
+        }
+
+        let (mut min, mut max, mut sum) = (i32::MAX, i32::MIN, 0i64);
+        for deviation in self.deviations() {
+            min = min.min(deviation);
+            max = max.max(deviation);
+            sum += i64::from(deviation);
+        }
+        (min, max, (sum / self.len as i64) as i32)
+    }
+
+    /// The percentage of recorded pin operations within ±10 µs of their
+    /// expected time, 100 if nothing was recorded.
+    pub fn quality_score(&self) -> u8 {
+        if self.len == 0 {
+            return 100;
+        }
+
+        let within = self
+            .deviations()
+            .filter(|deviation| deviation.abs() <= TOLERANCE_US)
+            .count();
+        (within * 100 / self.len) as u8
+    }
+
+    /// The deviation of every recorded pin operation in microseconds.
+    fn deviations(&self) -> impl Iterator<Item = i32> + '_ {
+        let edges = &self.edges[..self.len];
+        let start = edges.first().map_or(Instant::from_ticks(0), |e| e.1);
+        edges.iter().map(move |&(expected, at)| {
+            let actual = (at - start).to_micros() as i64;
+            (actual - expected as i64).clamp(i32::MIN.into(), i32::MAX.into())
+                as i32
+        })
+    }
+}
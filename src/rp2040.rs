@@ -0,0 +1,225 @@
+//! Sends commands with a PIO state machine of the RP2040, which times the
+//! pulses in hardware instead of blocking the CPU with a delay.
+
+use crate::ch8803::{
+    Channel, Command, CommandPacket, DEFAULT_INTER_PACKET_GAP, Duration,
+    InstantFn, MAX_TIMINGS, TIMING_PER_US, channel_commands,
+};
+use rp2040_hal::gpio::{AnyPin, SpecificPin};
+use rp2040_hal::pio::{
+    InstallError, PIO, PIOBuilder, PIOExt, PinDir, Running, Rx, ShiftDirection,
+    StateMachine, StateMachineIndex, Tx, UninitStateMachine,
+};
+
+/// The number of state machine cycles per FIFO word in addition to the
+/// count, for the `out` instructions and the last `jmp`.
+const WORD_OVERHEAD_US: u64 = 3;
+
+/// Sends commands to CH8803 receivers through a PIO state machine. Every
+/// pulse is pushed into the TX FIFO as the level in bit 0 and the length in
+/// the remaining bits, and the state machine holds the pin for exactly that
+/// long, so interrupts during a command do not distort the signal. Only the
+/// default encoding of a [Transmitter](crate::ch8803::Transmitter) is
+/// supported, with the same repetition settings.
+pub struct PioTransmitter<P, SM, PIN, NOW>
+where
+    P: PIOExt,
+    SM: StateMachineIndex,
+    PIN: AnyPin<Function = P::PinFunction>,
+    NOW: InstantFn,
+{
+    sm: StateMachine<(P, SM), Running>,
+    rx: Rx<(P, SM)>,
+    tx: Tx<(P, SM)>,
+    pin: SpecificPin<PIN>,
+    now_fn: NOW,
+    id: u16,
+    min_repeats: u8,
+    inter_packet_gap: Duration,
+}
+
+impl<P, SM, PIN, NOW> PioTransmitter<P, SM, PIN, NOW>
+where
+    P: PIOExt,
+    SM: StateMachineIndex,
+    PIN: AnyPin<Function = P::PinFunction>,
+    NOW: InstantFn,
+{
+    /// Installs the pulse program into `pio` and starts it on `sm`, driving
+    /// `pin`. The state machine runs at 1 MHz, derived from the system clock
+    /// of `sys_clock_hz`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PIO has no room for the program.
+    pub fn new(
+        pio: &mut PIO<P>,
+        sm: UninitStateMachine<(P, SM)>,
+        pin: PIN,
+        sys_clock_hz: u32,
+        now_fn: NOW,
+        id: u16,
+    ) -> Result<Self, InstallError> {
+        // out pins, 1; out x, 31; loop: jmp x-- loop
+        let mut a = pio::Assembler::<{ pio::RP2040_MAX_PROGRAM_SIZE }>::new();
+        let mut wrap_target = a.label();
+        let mut wrap_source = a.label();
+        let mut hold = a.label();
+        a.bind(&mut wrap_target);
+        a.out(pio::OutDestination::PINS, 1);
+        a.out(pio::OutDestination::X, 31);
+        a.bind(&mut hold);
+        a.jmp(pio::JmpCondition::XDecNonZero, &mut hold);
+        a.bind(&mut wrap_source);
+        let program =
+            pio.install(&a.assemble_with_wrap(wrap_source, wrap_target))?;
+
+        let pin: SpecificPin<PIN> = pin.into();
+        let pin_id = pin.id().num;
+        let int = sys_clock_hz / 1_000_000;
+        let frac = (sys_clock_hz % 1_000_000) * 256 / 1_000_000;
+        let (mut sm, rx, tx) = PIOBuilder::from_installed_program(program)
+            .out_pins(pin_id, 1)
+            .out_shift_direction(ShiftDirection::Right)
+            .autopull(true)
+            .pull_threshold(32)
+            .clock_divisor_fixed_point(int as u16, frac as u8)
+            .build(sm);
+        sm.set_pindirs([(pin_id, PinDir::Output)]);
+
+        Ok(Self {
+            sm: sm.start(),
+            rx,
+            tx,
+            pin,
+            now_fn,
+            id,
+            min_repeats: 1,
+            inter_packet_gap: DEFAULT_INTER_PACKET_GAP,
+        })
+    }
+
+    /// Stops the state machine and returns it together with the pin. The
+    /// program stays installed.
+    pub fn into_parts(self) -> (UninitStateMachine<(P, SM)>, SpecificPin<PIN>) {
+        let (sm, _program) = self.sm.uninit(self.rx, self.tx);
+        (sm, self.pin)
+    }
+
+    /// The ID of the device.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Sends at least `min_repeats` repetitions of every packet, see
+    /// [Transmitter::set_min_repeats](crate::ch8803::Transmitter::set_min_repeats).
+    pub fn set_min_repeats(&mut self, min_repeats: u8) {
+        self.min_repeats = min_repeats;
+    }
+
+    /// Holds the pin low for `gap` after every repetition of a packet, see
+    /// [Transmitter::set_inter_packet_gap](crate::ch8803::Transmitter::set_inter_packet_gap).
+    pub fn set_inter_packet_gap(&mut self, gap: Duration) {
+        self.inter_packet_gap = gap;
+    }
+
+    /// Binds the transmitter to a specific channel, see
+    /// [Transmitter::channel](crate::ch8803::Transmitter::channel).
+    #[allow(clippy::type_complexity)]
+    pub fn channel<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+    ) -> Result<PioChannelTransmitter<'_, P, SM, PIN, NOW>, C::Error> {
+        Ok(PioChannelTransmitter {
+            device: self,
+            channel: channel.try_into()?,
+        })
+    }
+
+    /// Sends a command for the given duration. Fails if the channel is an
+    /// index out of range, see
+    /// [Transmitter::send_command](crate::ch8803::Transmitter::send_command).
+    pub fn send_command<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> Result<(), C::Error> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: channel.try_into()?,
+            command,
+            strength,
+        };
+        self.send_packet(&packet, duration);
+        Ok(())
+    }
+
+    /// Sends a packet for the given duration, using the ID stored in the
+    /// packet. Returns once the last packet is queued, which is up to a few
+    /// pulses before it is sent.
+    pub fn send_packet(
+        &mut self,
+        packet: &CommandPacket,
+        duration: impl Into<Duration>,
+    ) {
+        let mut timings = [0; MAX_TIMINGS];
+        let len = packet.encode(&mut timings);
+
+        let gap_us = u64::from(self.inter_packet_gap.to_micros());
+        let mut repetitions = 0;
+        let end = (self.now_fn)() + duration.into();
+        while repetitions < self.min_repeats || (self.now_fn)() < end {
+            for (i, &timing) in timings[..len].iter().enumerate() {
+                if timing == 0 {
+                    continue;
+                }
+                let us = u64::from(timing) / TIMING_PER_US;
+                self.push(i % 2 == 1, us);
+            }
+            self.push(false, gap_us);
+            repetitions = repetitions.saturating_add(1);
+        }
+        // Leaves the pin low once the FIFO runs empty
+        self.push(false, WORD_OVERHEAD_US);
+    }
+
+    /// Queues a pulse of `us` microseconds, waiting for room in the FIFO.
+    fn push(&mut self, level: bool, us: u64) {
+        let count = us
+            .saturating_sub(WORD_OVERHEAD_US)
+            .min(u64::from(u32::MAX >> 1));
+        let word = (count as u32) << 1 | u32::from(level);
+        while !self.tx.write(word) {}
+    }
+}
+
+/// A [PioTransmitter] bound to a channel, the PIO counterpart of a
+/// [ChannelTransmitter](crate::ch8803::ChannelTransmitter).
+pub struct PioChannelTransmitter<'b, P, SM, PIN, NOW>
+where
+    P: PIOExt,
+    SM: StateMachineIndex,
+    PIN: AnyPin<Function = P::PinFunction>,
+    NOW: InstantFn,
+{
+    device: &'b mut PioTransmitter<P, SM, PIN, NOW>,
+    channel: Channel,
+}
+
+impl<P, SM, PIN, NOW> PioChannelTransmitter<'_, P, SM, PIN, NOW>
+where
+    P: PIOExt,
+    SM: StateMachineIndex,
+    PIN: AnyPin<Function = P::PinFunction>,
+    NOW: InstantFn,
+{
+    channel_commands!(mut);
+
+    fn send(&mut self, command: Command, strength: u8, duration: Duration) {
+        let Ok(()) =
+            self.device
+                .send_command(self.channel, command, strength, duration);
+    }
+}
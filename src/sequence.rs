@@ -0,0 +1,140 @@
+use crate::ch8803::{Channel, Command, Duration, InstantFn, Transmitter};
+use crate::encoder::Encoder;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+
+/// A single step of a [CommandSequence].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SequenceStep {
+    /// Sends a command for the duration, like
+    /// [send_command](Transmitter::send_command).
+    Command {
+        channel: Channel,
+        command: Command,
+        strength: u8,
+        duration: Duration,
+    },
+    /// Sends nothing for the duration.
+    Pause(Duration),
+}
+
+/// Up to `N` commands and pauses that are sent in order by
+/// [execute](Self::execute), e.g. a fixed pattern of a vibration, a pause
+/// and a shock. Needs no allocator.
+///
+/// ```ignore
+/// let sequence = CommandSequence::<3>::new()
+///     .push(Channel::Channel1, Command::Vibrate, 30, Duration::millis(500))
+///     .push_delay(Duration::millis(1000))
+///     .push(Channel::Channel1, Command::Shock, 10, Duration::millis(200));
+/// sequence.execute(&mut transmitter);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CommandSequence<const N: usize> {
+    steps: [Option<SequenceStep>; N],
+    len: usize,
+}
+
+impl<const N: usize> CommandSequence<N> {
+    pub const fn new() -> Self {
+        Self {
+            steps: [None; N],
+            len: 0,
+        }
+    }
+
+    /// Appends a command.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sequence already holds `N` steps.
+    pub fn push(
+        self,
+        channel: Channel,
+        command: Command,
+        strength: u8,
+        duration: Duration,
+    ) -> Self {
+        self.push_step(SequenceStep::Command {
+            channel,
+            command,
+            strength,
+            duration,
+        })
+    }
+
+    /// Appends a pause.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sequence already holds `N` steps.
+    pub fn push_delay(self, duration: Duration) -> Self {
+        self.push_step(SequenceStep::Pause(duration))
+    }
+
+    /// Appends a step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sequence already holds `N` steps.
+    pub fn push_step(mut self, step: SequenceStep) -> Self {
+        assert!(self.len < N, "the sequence is full");
+        self.steps[self.len] = Some(step);
+        self.len += 1;
+        self
+    }
+
+    /// The steps in the order they are sent.
+    pub fn steps(&self) -> impl Iterator<Item = SequenceStep> + '_ {
+        self.steps[..self.len].iter().flatten().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Sends all steps in order and returns the number of packets sent
+    /// without errors.
+    pub fn execute<PIN, DELAY, NOW, ENC, LED, BUSY>(
+        &self,
+        transmitter: &mut Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) -> u32
+    where
+        PIN: OutputPin,
+        DELAY: PulseDelay,
+        NOW: InstantFn,
+        ENC: Encoder,
+        LED: OutputPin,
+        BUSY: OutputPin,
+    {
+        let mut sent = 0;
+        for step in self.steps() {
+            match step {
+                SequenceStep::Command {
+                    channel,
+                    command,
+                    strength,
+                    duration,
+                } => {
+                    let Ok(step_sent) = transmitter
+                        .send_command(channel, command, strength, duration);
+                    sent += step_sent;
+                }
+                SequenceStep::Pause(duration) => {
+                    transmitter.wait_micros(duration.to_micros());
+                }
+            }
+        }
+        sent
+    }
+}
+
+impl<const N: usize> Default for CommandSequence<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
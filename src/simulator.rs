@@ -0,0 +1,125 @@
+use crate::capture::PacketDecoder;
+use crate::ch8803::{CommandPacket, Instant};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::convert::Infallible;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+/// A receiver without hardware, for integration tests on the host.
+///
+/// Connect a [Transmitter](crate::ch8803::Transmitter) to its
+/// [pin](Self::pin), [delay](Self::delay) and [now_fn](Self::now_fn). The
+/// delay advances a virtual clock instead of waiting, and every complete
+/// packet seen on the pin is recorded with the time its sync pulse started.
+#[derive(Default)]
+pub struct SimulatedCollar {
+    state: RefCell<State>,
+}
+
+#[derive(Default)]
+struct State {
+    now: u64,
+    level: bool,
+    sync_at: u64,
+    decoder: PacketDecoder,
+    received: Vec<(Instant, CommandPacket)>,
+}
+
+impl SimulatedCollar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The output pin to pass to the Transmitter.
+    pub fn pin(&self) -> SimulatorPin<'_> {
+        SimulatorPin { collar: self }
+    }
+
+    /// The delay to pass to the Transmitter.
+    pub fn delay(&self) -> SimulatorDelay<'_> {
+        SimulatorDelay { collar: self }
+    }
+
+    /// A `now_fn` reading the virtual clock.
+    pub fn now_fn(&self) -> impl Fn() -> Instant + '_ {
+        move || Instant::from_ticks(self.state.borrow().now)
+    }
+
+    /// All packet repetitions received so far.
+    pub fn received(&self) -> Vec<(Instant, CommandPacket)> {
+        self.state.borrow().received.clone()
+    }
+
+    /// Forgets all received packets.
+    pub fn clear(&self) {
+        self.state.borrow_mut().received.clear();
+    }
+
+    /// Panics if `packet` was not received at least once.
+    pub fn assert_received(&self, packet: CommandPacket) {
+        let state = self.state.borrow();
+        assert!(
+            state.received.iter().any(|(_, p)| *p == packet),
+            "{:?} was not received, got {:?}",
+            packet,
+            state.received,
+        );
+    }
+
+    fn pulse(&self, ns: u64) {
+        let us = ns / 1000;
+        let mut state = self.state.borrow_mut();
+        let start = state.now;
+        state.now += us;
+        if !state.level {
+            return;
+        }
+
+        // The long high pulse of the preamble starts a new packet
+        if PacketDecoder::is_sync(us) {
+            state.sync_at = start;
+        }
+        if let Some(packet) = state.decoder.high_pulse(us) {
+            let at = Instant::from_ticks(state.sync_at);
+            state.received.push((at, packet));
+        }
+    }
+}
+
+/// The output pin of a [SimulatedCollar].
+pub struct SimulatorPin<'a> {
+    collar: &'a SimulatedCollar,
+}
+
+impl ErrorType for SimulatorPin<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for SimulatorPin<'_> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.collar.state.borrow_mut().level = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.collar.state.borrow_mut().level = true;
+        Ok(())
+    }
+}
+
+/// The delay of a [SimulatedCollar].
+pub struct SimulatorDelay<'a> {
+    collar: &'a SimulatedCollar,
+}
+
+impl DelayNs for SimulatorDelay<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.collar.pulse(ns.into());
+    }
+
+    // Overridden so that long pulses are not split into several
+    fn delay_us(&mut self, us: u32) {
+        self.collar.pulse(u64::from(us) * 1000);
+    }
+}
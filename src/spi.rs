@@ -0,0 +1,168 @@
+use crate::ch8803::{
+    Channel, Command, CommandPacket, DEFAULT_INTER_PACKET_GAP, Duration,
+    InstantFn, MAX_TIMINGS, TIMING_PER_US,
+};
+use embedded_hal::spi::SpiBus;
+
+/// The number of bytes written to the bus at once.
+const CHUNK: usize = 32;
+
+/// An error of [send_command](SpiTransmitter::send_command).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpiCommandError<E, C> {
+    /// Writing to the bus failed.
+    Spi(E),
+    /// The channel could not be converted, e.g. a
+    /// [ChannelError](crate::ch8803::ChannelError) for an index out of
+    /// range.
+    Channel(C),
+}
+
+/// Sends commands to CH8803 receivers through the MOSI pin of an SPI bus
+/// instead of a GPIO pin. Every bit on the bus holds the level for
+/// `bit_us` microseconds, so the bus has to run at `1 / bit_us` MHz, e.g.
+/// 250 kHz for the 4 µs that divide all CH8803 timings. The signal is timed
+/// by the SPI clock, so no delay is needed.
+///
+/// The bus has to send consecutive words without gaps, e.g. through a FIFO
+/// or DMA, and keep MOSI low while idle. Only the default encoding of a
+/// [Transmitter](crate::ch8803::Transmitter) is supported, with the same
+/// repetition settings.
+pub struct SpiTransmitter<SPI: SpiBus, NOW: InstantFn> {
+    spi: SPI,
+    now_fn: NOW,
+    id: u16,
+    bit_us: u16,
+    min_repeats: u8,
+    inter_packet_gap: Duration,
+}
+
+impl<SPI: SpiBus, NOW: InstantFn> SpiTransmitter<SPI, NOW> {
+    /// Creates a transmitter for a bus with a bit period of `bit_us`
+    /// microseconds. Timings that are not a multiple of it are rounded down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_us` is zero.
+    pub fn new(spi: SPI, now_fn: NOW, id: u16, bit_us: u16) -> Self {
+        assert!(bit_us > 0, "the bit period must not be zero");
+        Self {
+            spi,
+            now_fn,
+            id,
+            bit_us,
+            min_repeats: 1,
+            inter_packet_gap: DEFAULT_INTER_PACKET_GAP,
+        }
+    }
+
+    /// Returns the SPI bus.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+
+    /// Sends at least `min_repeats` repetitions of every packet, see
+    /// [Transmitter::set_min_repeats](crate::ch8803::Transmitter::set_min_repeats).
+    pub fn set_min_repeats(&mut self, min_repeats: u8) {
+        self.min_repeats = min_repeats;
+    }
+
+    /// Holds MOSI low for `gap` after every repetition of a packet, see
+    /// [Transmitter::set_inter_packet_gap](crate::ch8803::Transmitter::set_inter_packet_gap).
+    pub fn set_inter_packet_gap(&mut self, gap: Duration) {
+        self.inter_packet_gap = gap;
+    }
+
+    /// Sends a command for the given duration. Fails if the channel is an
+    /// index out of range, see
+    /// [Transmitter::send_command](crate::ch8803::Transmitter::send_command),
+    /// or if writing to the bus fails.
+    pub fn send_command<C: TryInto<Channel>>(
+        &mut self,
+        channel: C,
+        command: Command,
+        strength: u8,
+        duration: impl Into<Duration>,
+    ) -> Result<(), SpiCommandError<SPI::Error, C::Error>> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: channel.try_into().map_err(SpiCommandError::Channel)?,
+            command,
+            strength,
+        };
+        self.send_packet(&packet, duration)
+            .map_err(SpiCommandError::Spi)
+    }
+
+    /// Sends a packet for the given duration, using the ID stored in the
+    /// packet.
+    pub fn send_packet(
+        &mut self,
+        packet: &CommandPacket,
+        duration: impl Into<Duration>,
+    ) -> Result<(), SPI::Error> {
+        let mut timings = [0; MAX_TIMINGS];
+        let len = packet.encode(&mut timings);
+
+        let bit_us = u64::from(self.bit_us);
+        let gap_bits = u64::from(self.inter_packet_gap.to_micros()) / bit_us;
+        let mut repetitions = 0;
+        let end = (self.now_fn)() + duration.into();
+        while repetitions < self.min_repeats || (self.now_fn)() < end {
+            let mut bits = BitWriter::new(&mut self.spi);
+            for (i, &timing) in timings[..len].iter().enumerate() {
+                let us = u64::from(timing) / TIMING_PER_US;
+                bits.push(i % 2 == 1, us / bit_us)?;
+            }
+            bits.push(false, gap_bits)?;
+            bits.flush()?;
+            self.spi.flush()?;
+            repetitions = repetitions.saturating_add(1);
+        }
+        Ok(())
+    }
+}
+
+/// Packs levels into bytes, MSB first, and writes them to the bus in chunks.
+struct BitWriter<'a, SPI: SpiBus> {
+    spi: &'a mut SPI,
+    buffer: [u8; CHUNK],
+    /// The number of bits in the buffer.
+    len: usize,
+}
+
+impl<'a, SPI: SpiBus> BitWriter<'a, SPI> {
+    fn new(spi: &'a mut SPI) -> Self {
+        Self {
+            spi,
+            buffer: [0; CHUNK],
+            len: 0,
+        }
+    }
+
+    /// Appends `count` bits of `level`.
+    fn push(&mut self, level: bool, count: u64) -> Result<(), SPI::Error> {
+        for _ in 0..count {
+            if level {
+                self.buffer[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+            if self.len == CHUNK * 8 {
+                self.spi.write(&self.buffer)?;
+                self.buffer = [0; CHUNK];
+                self.len = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining bits, padded with the low level.
+    fn flush(&mut self) -> Result<(), SPI::Error> {
+        if self.len > 0 {
+            self.spi.write(&self.buffer[..self.len.div_ceil(8)])?;
+            self.buffer = [0; CHUNK];
+            self.len = 0;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+/// Level `n` at index `n - 1` mapped to its perceived intensity, following
+/// `1 + 98 * log10(1 + 9 * (n - 1) / 98)`.
+#[rustfmt::skip]
+const PERCEPTUAL: [u8; 99] = [
+    1, 5, 8, 11, 14, 17, 20, 22, 24, 27,
+    29, 31, 33, 34, 36, 38, 39, 41, 43, 44,
+    45, 47, 48, 49, 51, 52, 53, 54, 55, 56,
+    57, 58, 59, 60, 61, 62, 63, 64, 65, 66,
+    67, 67, 68, 69, 70, 71, 71, 72, 73, 74,
+    74, 75, 76, 76, 77, 78, 78, 79, 80, 80,
+    81, 81, 82, 82, 83, 84, 84, 85, 85, 86,
+    86, 87, 87, 88, 88, 89, 89, 90, 90, 91,
+    91, 92, 92, 93, 93, 94, 94, 94, 95, 95,
+    96, 96, 97, 97, 97, 98, 98, 99, 99,
+];
+
+/// The inverse of [PERCEPTUAL].
+#[rustfmt::skip]
+const LINEAR: [u8; 99] = [
+    1, 1, 2, 2, 2, 2, 3, 3, 3, 4,
+    4, 4, 5, 5, 5, 6, 6, 6, 7, 7,
+    8, 8, 8, 9, 9, 10, 10, 11, 11, 12,
+    12, 13, 13, 14, 14, 15, 15, 16, 17, 17,
+    18, 19, 19, 20, 21, 21, 22, 23, 24, 25,
+    25, 26, 27, 28, 29, 30, 31, 32, 33, 34,
+    35, 36, 37, 38, 39, 40, 41, 43, 44, 45,
+    47, 48, 49, 51, 52, 54, 55, 57, 58, 60,
+    61, 63, 65, 67, 68, 70, 72, 74, 76, 78,
+    80, 82, 85, 87, 89, 92, 94, 96, 99,
+];
+
+/// Converts between the strength levels sent to the receiver and how strong
+/// they are perceived. The sensation grows roughly logarithmically with the
+/// level, so e.g. level 50 feels like about 74% of level 99.
+pub struct StrengthMapper;
+
+impl StrengthMapper {
+    /// Returns the perceived intensity (1–99) of the given level.
+    pub fn linear_to_perceptual(linear: u8) -> u8 {
+        lookup(&PERCEPTUAL, linear)
+    }
+
+    /// Returns the level (1–99) that is perceived with the given intensity.
+    pub fn perceptual_to_linear(perceptual: u8) -> u8 {
+        lookup(&LINEAR, perceptual)
+    }
+}
+
+/// Values above 99 are treated as 99, zero stays zero.
+fn lookup(table: &[u8; 99], value: u8) -> u8 {
+    match value {
+        0 => 0,
+        value => table[value.min(99) as usize - 1],
+    }
+}
+
+/// A strength level accepted by the receiver, 1–99.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Strength(u8);
+
+/// The reason a value is not a valid [Strength].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StrengthError {
+    /// The value is outside of the accepted range.
+    OutOfRange,
+}
+
+impl Strength {
+    pub const MIN: Strength = Strength(1);
+    pub const MAX: Strength = Strength(99);
+
+    /// Creates a strength from a level between 1 and 99.
+    pub fn new(level: u8) -> Result<Self, StrengthError> {
+        match level {
+            1..=99 => Ok(Self(level)),
+            _ => Err(StrengthError::OutOfRange),
+        }
+    }
+
+    /// The level sent to the receiver.
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    /// The level as rounded percentage of the maximum, 1–100. Level 1 is 1%,
+    /// 50 is 51% and 99 is 100%.
+    pub fn percent_u8(&self) -> u8 {
+        ((self.0 as u16 * 100 + 49) / 99) as u8
+    }
+
+    /// Creates the strength closest to a percentage between 1 and 100. This
+    /// is the inverse of [percent_u8](Self::percent_u8).
+    pub fn from_percent(pct: u8) -> Result<Self, StrengthError> {
+        match pct {
+            1..=100 => Ok(Self(((pct as u16 * 99 + 50) / 100) as u8)),
+            _ => Err(StrengthError::OutOfRange),
+        }
+    }
+
+    /// The level as percentage of the maximum, without rounding.
+    #[cfg(feature = "std")]
+    pub fn percentage(&self) -> f32 {
+        f32::from(self.0) * 100.0 / 99.0
+    }
+}
+
+impl From<Strength> for u8 {
+    fn from(strength: Strength) -> u8 {
+        strength.0
+    }
+}
+
+/// Deserializes the level as a plain number, rejecting levels outside of
+/// 1–99 like [new](Strength::new).
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Strength {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let level = u8::deserialize(deserializer)?;
+        Strength::new(level).map_err(|_| {
+            serde::de::Error::custom("strength is not between 1 and 99")
+        })
+    }
+}
+
+/// How [adaptive_shock](crate::ch8803::ChannelTransmitter::adaptive_shock)
+/// raises the strength while the shock is not confirmed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveStrengthPolicy {
+    /// The strength of the first step.
+    pub start: u8,
+
+    /// The strength is never raised above this level.
+    pub max: u8,
+
+    /// The amount the strength is raised by with every step.
+    pub step: u8,
+
+    /// The length of every step.
+    pub step_interval_ms: u32,
+}
+
+impl AdaptiveStrengthPolicy {
+    /// The strength of the given step, starting at zero.
+    pub fn strength_at(&self, step: u32) -> u8 {
+        let raised = u32::from(self.start) + u32::from(self.step) * step;
+        raised.min(u32::from(self.max)) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_table() {
+        for (level, percent) in [(1, 1), (50, 51), (99, 100)] {
+            let strength = Strength::new(level).unwrap();
+            assert_eq!(strength.percent_u8(), percent, "level {level}");
+        }
+    }
+
+    #[test]
+    fn from_percent_inverts_percent_u8() {
+        for level in 1..=99 {
+            let strength = Strength::new(level).unwrap();
+            assert_eq!(
+                Strength::from_percent(strength.percent_u8()),
+                Ok(strength)
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let strength = Strength::new(50).unwrap();
+        let json = serde_json::to_string(&strength).unwrap();
+        assert_eq!(json, "50");
+        assert_eq!(serde_json::from_str::<Strength>(&json).unwrap(), strength);
+
+        assert!(serde_json::from_str::<Strength>("0").is_err());
+        assert!(serde_json::from_str::<Strength>("100").is_err());
+    }
+}
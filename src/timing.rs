@@ -0,0 +1,178 @@
+use crate::ch8803::{
+    Channel, Command, CommandPacket, DEFAULT_INTER_PACKET_GAP, Duration,
+    Instant, InstantFn, TIMING_PER_US, Timing, Transmitter, TransmitterConfig,
+};
+use core::cell::RefCell;
+use core::fmt;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// A blocking delay for pulses of a given [Timing]. This is implemented for
+/// all `DelayNs` implementations, which wait with `delay_us`, or with
+/// `delay_ns` with the `nanosecond-timing` feature.
+pub trait PulseDelay {
+    fn delay_pulse(&mut self, timing: Timing);
+}
+
+impl<D: DelayNs> PulseDelay for D {
+    fn delay_pulse(&mut self, timing: Timing) {
+        // Timing is narrower than u32 with the u16-timing feature
+        #[cfg(not(feature = "nanosecond-timing"))]
+        #[allow(clippy::useless_conversion)]
+        self.delay_us(timing.into());
+        #[cfg(feature = "nanosecond-timing")]
+        self.delay_ns(timing);
+    }
+}
+
+/// A single peripheral that provides both the blocking delay and the current
+/// time for a transmitter.
+///
+/// A [Transmitter] still takes the delay and the `now_fn` as two generic
+/// parameters instead of one TimingSource: the delay is borrowed through a
+/// `RefCell` so that other drivers can share it, and `NOW` is part of the
+/// type of every wrapper and bound transmitter, which would all break.
+/// [transmitter] creates a Transmitter from a single TimingSource instead,
+/// without writing a `now_fn`.
+pub trait TimingSource: PulseDelay {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// Creates a `now_fn` that reads the time from a shared [TimingSource]. This
+/// allows passing the same peripheral as `delay` and `now_fn` to the
+/// [Transmitter](crate::ch8803::Transmitter) builder.
+pub fn now_fn<TS: TimingSource>(
+    source: &RefCell<TS>,
+) -> impl Fn() -> Instant + '_ {
+    move || source.borrow().now()
+}
+
+/// Creates a [Transmitter] with the default settings that takes both the
+/// delay and the current time from `source`, like
+/// [Transmitter::from_config] with [now_fn].
+pub fn transmitter<'a, PIN: OutputPin, TS: TimingSource>(
+    pin: PIN,
+    source: &'a RefCell<TS>,
+    id: u16,
+) -> Transmitter<'a, PIN, TS, impl InstantFn + 'a> {
+    Transmitter::from_config(TransmitterConfig {
+        pin,
+        delay: source,
+        now_fn: now_fn(source),
+        id,
+    })
+}
+
+/// Adapter implementing [TimingSource] for HAL timers that count in
+/// microseconds.
+#[derive(Clone, Copy)]
+pub struct FugitTimer<T>(pub T);
+
+impl<T: DelayNs> DelayNs for FugitTimer<T> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms);
+    }
+}
+
+#[cfg(feature = "rp2040")]
+impl TimingSource for FugitTimer<rp2040_hal::Timer> {
+    fn now(&self) -> Instant {
+        self.0.get_counter()
+    }
+}
+
+/// A timing source without any hardware behind it. Delays return immediately
+/// and advance the clock by the requested time, which makes it suitable for
+/// tests on the host.
+#[derive(Clone, Copy, Default)]
+pub struct MockTimingSource {
+    /// The elapsed time in [Timing] units.
+    ticks: u64,
+}
+
+impl DelayNs for MockTimingSource {
+    fn delay_ns(&mut self, ns: u32) {
+        self.ticks += u64::from(ns) * TIMING_PER_US / 1000;
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.ticks += u64::from(us) * TIMING_PER_US;
+    }
+}
+
+impl TimingSource for MockTimingSource {
+    fn now(&self) -> Instant {
+        Instant::from_ticks(self.ticks / TIMING_PER_US)
+    }
+}
+
+/// Wrapper for printing a [Duration] in the largest unit that represents it
+/// exactly, e.g. `2s`, `250ms` or `840us`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DisplayDuration(pub Duration);
+
+impl fmt::Display for DisplayDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let us = self.0.to_micros();
+        if us != 0 && us % 1_000_000 == 0 {
+            write!(f, "{}s", us / 1_000_000)
+        } else if us % 1_000 == 0 {
+            write!(f, "{}ms", us / 1_000)
+        } else {
+            write!(f, "{}us", us)
+        }
+    }
+}
+
+/// Extension methods for [Duration].
+pub trait DurationExt {
+    /// Returns a wrapper implementing `Display`.
+    fn display(self) -> DisplayDuration;
+
+    /// The time it takes to send the packet `n` times with the default
+    /// settings of a [Transmitter](crate::ch8803::Transmitter), e.g. to
+    /// send a command exactly 5 times: `n` packets and the default
+    /// inter-packet gap between each two of them. Saturates at the maximum
+    /// duration.
+    fn from_packet_count(
+        n: u32,
+        id: u16,
+        channel: Channel,
+        command: Command,
+        strength: u8,
+    ) -> Self;
+}
+
+impl DurationExt for Duration {
+    fn display(self) -> DisplayDuration {
+        DisplayDuration(self)
+    }
+
+    fn from_packet_count(
+        n: u32,
+        id: u16,
+        channel: Channel,
+        command: Command,
+        strength: u8,
+    ) -> Self {
+        let packet = CommandPacket {
+            id,
+            channel,
+            command,
+            strength,
+        };
+        let gap = u64::from(DEFAULT_INTER_PACKET_GAP.to_micros());
+        let us = u64::from(packet.total_duration_us()) * u64::from(n)
+            + gap * u64::from(n.saturating_sub(1));
+        Duration::micros(u32::try_from(us).unwrap_or(u32::MAX))
+    }
+}
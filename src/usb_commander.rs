@@ -0,0 +1,368 @@
+//! Text commands over a USB serial port, e.g. typed into a terminal.
+//!
+//! Unlike the C, WebAssembly and BLE interfaces, which take the channel
+//! index 0–2 like `Channel::try_from`, channels are numbered **1–3** here,
+//! as on the original remote, see [Channel::from_number].
+
+use crate::ch8803::{Channel, InstantFn, Transmitter};
+use crate::encoder::Encoder;
+use crate::timing::PulseDelay;
+use embedded_hal::digital::OutputPin;
+use usb_device::bus::UsbBus;
+use usb_device::device::UsbDevice;
+use usbd_serial::SerialPort;
+
+/// The longest command line that is accepted, without the line break.
+const LINE_LEN: usize = 64;
+
+/// A command received by a [UsbCommander]. Channels are numbered 1–3 as on
+/// the original remote.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UsbCommand {
+    /// `shock <channel> <strength> <duration_ms>`
+    Shock {
+        channel: Channel,
+        strength: u8,
+        duration_ms: u32,
+    },
+    /// `vibrate <channel> <strength> <duration_ms>`
+    Vibrate {
+        channel: Channel,
+        strength: u8,
+        duration_ms: u32,
+    },
+    /// `beep <channel> <duration_ms>`
+    Beep { channel: Channel, duration_ms: u32 },
+}
+
+/// The reason a line is not a valid [UsbCommand].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParseError {
+    /// The first word is not a known command.
+    UnknownCommand,
+    /// The command has too few arguments.
+    MissingArgument,
+    /// The command has too many arguments.
+    TooManyArguments,
+    /// An argument is not a number or too large.
+    InvalidNumber,
+    /// The channel is not 1, 2 or 3.
+    InvalidChannel,
+    /// The line is longer than 64 bytes or not valid UTF-8.
+    InvalidLine,
+}
+
+impl ParseError {
+    fn as_str(self) -> &'static str {
+        match self {
+            ParseError::UnknownCommand => "unknown command",
+            ParseError::MissingArgument => "missing argument",
+            ParseError::TooManyArguments => "too many arguments",
+            ParseError::InvalidNumber => "invalid number",
+            ParseError::InvalidChannel => "invalid channel",
+            ParseError::InvalidLine => "invalid line",
+        }
+    }
+}
+
+impl UsbCommand {
+    /// Parses a line of whitespace-separated words, e.g. `shock 1 50 500`.
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        let mut words = line.split_ascii_whitespace();
+        let name = words.next().ok_or(ParseError::UnknownCommand)?;
+        if !matches!(name, "shock" | "vibrate" | "beep") {
+            return Err(ParseError::UnknownCommand);
+        }
+        let mut number = || {
+            words
+                .next()
+                .ok_or(ParseError::MissingArgument)?
+                .parse::<u32>()
+                .map_err(|_| ParseError::InvalidNumber)
+        };
+
+        let channel = u8::try_from(number()?)
+            .ok()
+            .and_then(|number| Channel::from_number(number).ok())
+            .ok_or(ParseError::InvalidChannel)?;
+        let command = match name {
+            "shock" | "vibrate" => {
+                let strength = u8::try_from(number()?)
+                    .map_err(|_| ParseError::InvalidNumber)?;
+                let duration_ms = number()?;
+                if name == "shock" {
+                    UsbCommand::Shock {
+                        channel,
+                        strength,
+                        duration_ms,
+                    }
+                } else {
+                    UsbCommand::Vibrate {
+                        channel,
+                        strength,
+                        duration_ms,
+                    }
+                }
+            }
+            _ => UsbCommand::Beep {
+                channel,
+                duration_ms: number()?,
+            },
+        };
+
+        match words.next() {
+            Some(_) => Err(ParseError::TooManyArguments),
+            None => Ok(command),
+        }
+    }
+}
+
+/// Controls a [Transmitter] with text commands from a USB serial port, one
+/// [UsbCommand] per line. Every line is answered with `ok` or `error: `
+/// followed by the reason.
+///
+/// Commands block until they are sent, during which the USB device is not
+/// polled. Hosts usually tolerate this, but long durations may cause
+/// timeouts.
+pub struct UsbCommander<'a, B: UsbBus> {
+    device: UsbDevice<'a, B>,
+    serial: SerialPort<'a, B>,
+    line: LineBuffer,
+}
+
+/// Collects received bytes into lines.
+struct LineBuffer {
+    line: [u8; LINE_LEN],
+    len: usize,
+    overflow: bool,
+}
+
+impl LineBuffer {
+    const fn new() -> Self {
+        Self {
+            line: [0; LINE_LEN],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// Adds a received byte. Returns the parsed line once a line break
+    /// completes it; empty lines are ignored.
+    fn push(&mut self, byte: u8) -> Option<Result<UsbCommand, ParseError>> {
+        match byte {
+            b'\r' | b'\n' => {
+                let result =
+                    (self.len > 0 || self.overflow).then(|| self.parse());
+                self.len = 0;
+                self.overflow = false;
+                result
+            }
+            _ if self.len == LINE_LEN => {
+                self.overflow = true;
+                None
+            }
+            _ => {
+                self.line[self.len] = byte;
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    fn parse(&self) -> Result<UsbCommand, ParseError> {
+        if self.overflow {
+            return Err(ParseError::InvalidLine);
+        }
+        let line = core::str::from_utf8(&self.line[..self.len])
+            .map_err(|_| ParseError::InvalidLine)?;
+        UsbCommand::parse(line)
+    }
+}
+
+impl<'a, B: UsbBus> UsbCommander<'a, B> {
+    pub fn new(device: UsbDevice<'a, B>, serial: SerialPort<'a, B>) -> Self {
+        Self {
+            device,
+            serial,
+            line: LineBuffer::new(),
+        }
+    }
+
+    /// Polls the USB device and executes all complete lines received since the
+    /// last call. Call this from the main loop at least every 10 ms.
+    pub fn poll<PIN, DELAY, NOW, ENC, LED, BUSY>(
+        &mut self,
+        tx: &mut Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) where
+        PIN: OutputPin,
+        DELAY: PulseDelay,
+        NOW: InstantFn,
+        ENC: Encoder,
+        LED: OutputPin,
+        BUSY: OutputPin,
+    {
+        if !self.device.poll(&mut [&mut self.serial]) {
+            return;
+        }
+
+        let mut buf = [0; LINE_LEN];
+        let Ok(count) = self.serial.read(&mut buf) else {
+            return;
+        };
+        for &byte in &buf[..count] {
+            if let Some(result) = self.line.push(byte) {
+                self.execute(result, tx);
+            }
+        }
+    }
+
+    fn execute<PIN, DELAY, NOW, ENC, LED, BUSY>(
+        &mut self,
+        command: Result<UsbCommand, ParseError>,
+        tx: &mut Transmitter<'_, PIN, DELAY, NOW, ENC, LED, BUSY>,
+    ) where
+        PIN: OutputPin,
+        DELAY: PulseDelay,
+        NOW: InstantFn,
+        ENC: Encoder,
+        LED: OutputPin,
+        BUSY: OutputPin,
+    {
+        match command {
+            Ok(UsbCommand::Shock {
+                channel,
+                strength,
+                duration_ms,
+            }) => {
+                let Ok(mut collar) = tx.channel(channel);
+                collar.shock_ms(strength, duration_ms);
+            }
+            Ok(UsbCommand::Vibrate {
+                channel,
+                strength,
+                duration_ms,
+            }) => {
+                let Ok(mut collar) = tx.channel(channel);
+                collar.vibrate_ms(strength, duration_ms);
+            }
+            Ok(UsbCommand::Beep {
+                channel,
+                duration_ms,
+            }) => {
+                let Ok(mut collar) = tx.channel(channel);
+                collar.beep_ms(duration_ms);
+            }
+            Err(err) => {
+                self.write(b"error: ");
+                self.write(err.as_str().as_bytes());
+                self.write(b"\r\n");
+                return;
+            }
+        }
+        self.write(b"ok\r\n");
+    }
+
+    /// Writes as much of `bytes` as fits, replies are dropped if the host
+    /// does not read them.
+    fn write(&mut self, bytes: &[u8]) {
+        let _ = self.serial.write(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `bytes` into a LineBuffer and returns the result of the last
+    /// complete line.
+    fn feed(bytes: &[u8]) -> Option<Result<UsbCommand, ParseError>> {
+        let mut buffer = LineBuffer::new();
+        bytes.iter().filter_map(|&byte| buffer.push(byte)).last()
+    }
+
+    #[test]
+    fn parses_valid_lines() {
+        assert_eq!(
+            UsbCommand::parse("shock 1 50 500"),
+            Ok(UsbCommand::Shock {
+                channel: Channel::Channel1,
+                strength: 50,
+                duration_ms: 500,
+            })
+        );
+        assert_eq!(
+            UsbCommand::parse("  vibrate\t3 99   1000 "),
+            Ok(UsbCommand::Vibrate {
+                channel: Channel::Channel3,
+                strength: 99,
+                duration_ms: 1000,
+            })
+        );
+        assert_eq!(
+            UsbCommand::parse("beep 2 250"),
+            Ok(UsbCommand::Beep {
+                channel: Channel::Channel2,
+                duration_ms: 250,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        let cases = [
+            ("", ParseError::UnknownCommand),
+            ("zap 1 50 500", ParseError::UnknownCommand),
+            ("shock 1 50", ParseError::MissingArgument),
+            ("beep", ParseError::MissingArgument),
+            ("shock 1 50 500 1", ParseError::TooManyArguments),
+            ("beep 1 250 50", ParseError::TooManyArguments),
+            ("shock 0 50 500", ParseError::InvalidChannel),
+            ("shock 4 50 500", ParseError::InvalidChannel),
+            ("beep 257 250", ParseError::InvalidChannel),
+            ("shock 1 256 500", ParseError::InvalidNumber),
+            ("vibrate 1 -1 500", ParseError::InvalidNumber),
+            ("beep 1 5s", ParseError::InvalidNumber),
+        ];
+        for (line, err) in cases {
+            assert_eq!(UsbCommand::parse(line), Err(err), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn splits_lines() {
+        assert_eq!(feed(b"\r\n\n"), None);
+        assert_eq!(
+            feed(b"shock 9 1 1\r\nbeep 1 100\r\n"),
+            Some(Ok(UsbCommand::Beep {
+                channel: Channel::Channel1,
+                duration_ms: 100,
+            }))
+        );
+        assert_eq!(feed(b"beep 1 100"), None);
+        assert_eq!(
+            feed(b"beep \xff 100\n"),
+            Some(Err(ParseError::InvalidLine))
+        );
+    }
+
+    #[test]
+    fn rejects_overlong_line() {
+        let mut buffer = LineBuffer::new();
+        let line = [b'1'; LINE_LEN + 1];
+        assert!(line.iter().all(|&byte| buffer.push(byte).is_none()));
+        assert_eq!(buffer.push(b'\n'), Some(Err(ParseError::InvalidLine)));
+
+        // The next line is accepted again
+        for &byte in b"beep 3 10" {
+            buffer.push(byte);
+        }
+        assert_eq!(
+            buffer.push(b'\n'),
+            Some(Ok(UsbCommand::Beep {
+                channel: Channel::Channel3,
+                duration_ms: 10,
+            }))
+        );
+    }
+}
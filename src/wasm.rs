@@ -0,0 +1,92 @@
+use crate::ch8803::{Channel, Command, CommandPacket};
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+use web_sys::{CustomEvent, CustomEventInit};
+
+/// The name of the event dispatched on `window` for every command.
+pub const EVENT_NAME: &str = "zap";
+
+/// A transmitter for the browser. Instead of toggling a pin it dispatches a
+/// [EVENT_NAME] `CustomEvent` on `window`, whose `detail` holds the `id`,
+/// `channel`, `command`, `strength` and `durationMs` of the command.
+#[wasm_bindgen]
+pub struct JsTransmitter {
+    id: u16,
+    channel: Channel,
+}
+
+#[wasm_bindgen]
+impl JsTransmitter {
+    /// Creates a transmitter sending on channel 1 of the collar with `id`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(id: u16) -> Self {
+        Self {
+            id,
+            channel: Channel::Channel1,
+        }
+    }
+
+    /// Selects the channel (0-2) used for the following commands.
+    #[wasm_bindgen(js_name = setChannel)]
+    pub fn set_channel(&mut self, channel: u8) -> Result<(), JsError> {
+        self.channel = Channel::try_from(channel)
+            .map_err(|_| JsError::new("channel must be 0, 1 or 2"))?;
+        Ok(())
+    }
+
+    pub fn shock(&self, strength: u8, duration_ms: u32) -> Result<(), JsValue> {
+        self.dispatch(Command::Shock, strength, duration_ms)
+    }
+
+    pub fn vibrate(
+        &self,
+        strength: u8,
+        duration_ms: u32,
+    ) -> Result<(), JsValue> {
+        self.dispatch(Command::Vibrate, strength, duration_ms)
+    }
+
+    pub fn beep(&self, duration_ms: u32) -> Result<(), JsValue> {
+        self.dispatch(Command::Beep, 0, duration_ms)
+    }
+}
+
+impl JsTransmitter {
+    fn dispatch(
+        &self,
+        command: Command,
+        strength: u8,
+        duration_ms: u32,
+    ) -> Result<(), JsValue> {
+        let packet = CommandPacket {
+            id: self.id,
+            channel: self.channel,
+            command,
+            strength,
+        };
+        let command = match packet.command {
+            Command::Shock => "shock",
+            Command::Vibrate => "vibrate",
+            Command::Beep => "beep",
+        };
+
+        let detail = Object::new();
+        Reflect::set(&detail, &"id".into(), &packet.id.into())?;
+        Reflect::set(
+            &detail,
+            &"channel".into(),
+            &(packet.channel as u8).into(),
+        )?;
+        Reflect::set(&detail, &"command".into(), &command.into())?;
+        Reflect::set(&detail, &"strength".into(), &packet.strength.into())?;
+        Reflect::set(&detail, &"durationMs".into(), &duration_ms.into())?;
+
+        let init = CustomEventInit::new();
+        init.set_detail(&detail);
+        let event = CustomEvent::new_with_event_init_dict(EVENT_NAME, &init)?;
+        let window =
+            web_sys::window().ok_or_else(|| JsError::new("no window"))?;
+        window.dispatch_event(&event)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+/// One full sine period sampled at 64 points, scaled to 0–255 with 128 as the
+/// zero crossing.
+#[rustfmt::skip]
+pub const SIN_TABLE: &[u8] = &[
+    128, 140, 152, 165, 176, 188, 198, 208,
+    218, 226, 234, 240, 245, 250, 253, 254,
+    255, 254, 253, 250, 245, 240, 234, 226,
+    218, 208, 198, 188, 176, 165, 152, 140,
+    128, 115, 103, 90, 79, 67, 57, 47,
+    37, 29, 21, 15, 10, 5, 2, 1,
+    0, 1, 2, 5, 10, 15, 21, 29,
+    37, 47, 57, 67, 79, 90, 103, 115,
+];
+
+/// Looks up the sine of `phase` in [SIN_TABLE].
+///
+/// A full turn is 256 steps, so 0 is 0°, 64 is 90°, 128 is 180° and 255 is
+/// just short of 360°. The result is scaled to 0–255, with 128 being zero.
+pub const fn sin_u8(phase: u8) -> u8 {
+    SIN_TABLE[(phase >> 2) as usize]
+}
+
+/// Triangle wave of `phase`, using the same phase encoding as [sin_u8]. It
+/// rises linearly from 0 at 0° to 255 at 180° and falls back afterwards.
+pub const fn triangle_u8(phase: u8) -> u8 {
+    if phase < 128 {
+        phase * 2
+    } else {
+        (255 - phase) * 2 + 1
+    }
+}
@@ -0,0 +1,121 @@
+/// Scrambling of the payload bytes with a pseudo-noise sequence, for
+/// receivers that expect whitened data.
+///
+/// The sequence is generated by an 8-bit Galois LFSR starting at `seed`:
+/// for every bit, the state is shifted right and XORed with `polynomial` if
+/// the bit shifted out was set. Each byte of the sequence collects the bits
+/// shifted out, MSB first. A seed of zero produces no scrambling.
+///
+/// Whitening is its own inverse, so [apply](Self::apply) also restores the
+/// original bytes of a whitened payload.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataWhitening {
+    pub polynomial: u8,
+    pub seed: u8,
+}
+
+impl DataWhitening {
+    /// XORs `bytes` with the sequence, starting from the seed.
+    pub fn apply(&self, bytes: &mut [u8]) {
+        let mut state = self.seed;
+        for byte in bytes {
+            let mut mask = 0;
+            for _ in 0..8 {
+                let bit = state & 1;
+                mask = (mask << 1) | bit;
+                state >>= 1;
+                if bit != 0 {
+                    state ^= self.polynomial;
+                }
+            }
+            *byte ^= mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::PacketDecoder;
+    use crate::ch8803::{Channel, Command, CommandPacket, TIMING_PER_US};
+    use crate::indicator::NoPin;
+    use crate::timing::{self, MockTimingSource};
+    use core::cell::RefCell;
+
+    const WHITENING: DataWhitening = DataWhitening {
+        polynomial: 0xb8,
+        seed: 0x5a,
+    };
+
+    const PAYLOAD: [u8; 6] = [0x0d, 0x25, 0x01, 0x32, 0x65, 0x00];
+
+    #[test]
+    fn known_answer() {
+        // Whitening zeros yields the sequence itself
+        let mut bytes = [0; 5];
+        WHITENING.apply(&mut bytes);
+        assert_eq!(bytes, [0x5c, 0x0c, 0x93, 0x72, 0x0a]);
+
+        let mut bytes = PAYLOAD;
+        WHITENING.apply(&mut bytes);
+        assert_eq!(bytes, [0x51, 0x29, 0x92, 0x40, 0x6f, 0xda]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = PAYLOAD;
+        WHITENING.apply(&mut bytes);
+        assert_ne!(bytes, PAYLOAD);
+        WHITENING.apply(&mut bytes);
+        assert_eq!(bytes, PAYLOAD);
+    }
+
+    #[test]
+    fn zero_seed_is_identity() {
+        let whitening = DataWhitening {
+            seed: 0,
+            ..WHITENING
+        };
+        let mut bytes = PAYLOAD;
+        whitening.apply(&mut bytes);
+        assert_eq!(bytes, PAYLOAD);
+    }
+
+    #[test]
+    fn round_trip_through_decoder() {
+        let packet = CommandPacket {
+            id: 0x0d25,
+            channel: Channel::Channel2,
+            command: Command::Shock,
+            strength: 50,
+        };
+        let source = RefCell::new(MockTimingSource::default());
+        let mut transmitter = timing::transmitter(NoPin, &source, packet.id);
+        transmitter.set_whitening(Some(WHITENING));
+        let timings = transmitter.encode_command_packet(&packet);
+
+        // The timings alternate between low and high, starting low
+        let mut decoder = PacketDecoder::new();
+        let bits = timings
+            .as_slice()
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .find_map(|&high| {
+                decoder.high_pulse_bits(u64::from(high) / TIMING_PER_US)
+            })
+            .unwrap();
+        assert_eq!(CommandPacket::from_bits(bits), None);
+
+        // The five payload bytes are followed by two zero bits
+        let mut payload: [u8; 5] =
+            core::array::from_fn(|i| (bits >> (2 + 8 * (4 - i))) as u8);
+        WHITENING.apply(&mut payload);
+        let bits = payload
+            .iter()
+            .fold(0, |bits, &byte| bits << 8 | u64::from(byte))
+            << 2;
+        assert_eq!(CommandPacket::from_bits(bits), Some(packet));
+    }
+}
@@ -0,0 +1,64 @@
+//! Checks the checksum of encoded packets against the sum of their fields.
+
+use core::cell::RefCell;
+use proptest::prelude::*;
+use zap_me::ch8803::{Channel, Command, Timing};
+use zap_me::indicator::NoPin;
+use zap_me::timing::{self, MockTimingSource};
+
+/// The number of entries of the default preamble, which ends low.
+const PREAMBLE_LEN: usize = 3;
+/// The first bit of the checksum, after the ID, channel, command and strength.
+const CHECKSUM_BIT: usize = 16 + 4 + 4 + 8;
+
+/// Decodes 8 bits starting at bit `first` of a packet encoded with the
+/// [PwmEncoder](zap_me::encoder::PwmEncoder), where every bit is a high and
+/// a low entry and the high one is longer for a `1`.
+fn decode_byte(timings: &[Timing], first: usize) -> u8 {
+    let threshold = (292 + 804) / 2 * Timing::from(timing_per_us());
+    (first..first + 8).fold(0, |byte, bit| {
+        let high = timings[PREAMBLE_LEN + 2 * bit];
+        byte << 1 | u8::from(high > threshold)
+    })
+}
+
+#[cfg(not(feature = "nanosecond-timing"))]
+fn timing_per_us() -> u8 {
+    1
+}
+
+#[cfg(feature = "nanosecond-timing")]
+fn timing_per_us() -> u16 {
+    1000
+}
+
+proptest! {
+    #[test]
+    fn checksum_is_sum_of_fields(
+        id: u16,
+        channel in 0u8..3,
+        command in 1u8..=3,
+        strength in 1u8..=99,
+    ) {
+        let source = RefCell::new(MockTimingSource::default());
+        let transmitter = timing::transmitter(NoPin, &source, id);
+        let command_enum = match command {
+            1 => Command::Shock,
+            2 => Command::Vibrate,
+            _ => Command::Beep,
+        };
+        let timings = transmitter.encode_packet(
+            Channel::try_from(channel).unwrap(),
+            command_enum,
+            strength,
+        );
+
+        let [id_hi, id_lo] = id.to_be_bytes();
+        let expected = id_hi
+            .wrapping_add(id_lo)
+            .wrapping_add(channel)
+            .wrapping_add(command)
+            .wrapping_add(strength);
+        prop_assert_eq!(decode_byte(timings.as_slice(), CHECKSUM_BIT), expected);
+    }
+}
@@ -0,0 +1,57 @@
+//! Runs the `qemu_zap` example under QEMU and checks that it completes a
+//! vibrate and beep cycle, like `make qemu`. Ignored by default because it
+//! needs `qemu-system-arm` and the `thumbv7m-none-eabi` target; run it with
+//! `cargo test --test qemu_integration -- --ignored`.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the example may take to build and run before it is killed.
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+#[test]
+#[ignore = "needs qemu-system-arm and the thumbv7m-none-eabi target"]
+fn qemu_cycle_completes() {
+    let example =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/qemu_zap");
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let mut child = Command::new(cargo)
+        .args(["run", "--release"])
+        .current_dir(example)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start cargo");
+
+    // Drain stdout while the child runs, so it never blocks on a full pipe.
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).unwrap();
+        output
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if start.elapsed() > TIMEOUT {
+            child.kill().unwrap();
+            panic!("QEMU did not exit within {TIMEOUT:?}");
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    let output = reader.join().unwrap();
+    assert!(
+        status.success(),
+        "the firmware failed with {status}:\n{output}"
+    );
+    assert!(
+        output.contains("cycle complete"),
+        "no complete cycle reported:\n{output}",
+    );
+}
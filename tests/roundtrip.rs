@@ -0,0 +1,80 @@
+//! Encodes packets and decodes them again for every channel and command.
+
+use core::cell::RefCell;
+use zap_me::capture::PacketDecoder;
+use zap_me::ch8803::{Channel, Command, CommandPacket};
+use zap_me::indicator::NoPin;
+use zap_me::timing::{self, MockTimingSource};
+
+const IDS: [u16; 4] = [0x0001, 0x0d25, 0x7fff, 0xffff];
+const COMMANDS: [Command; 3] =
+    [Command::Shock, Command::Vibrate, Command::Beep];
+const STRENGTHS: [u8; 3] = [1, 50, 99];
+
+/// The length of the first, low pulse of the preamble in microseconds, used
+/// to convert the timings to microseconds for any timing feature.
+const PREAMBLE_LOW_US: u64 = 840;
+
+fn packets() -> impl Iterator<Item = CommandPacket> {
+    IDS.into_iter().flat_map(|id| {
+        Channel::ALL.into_iter().flat_map(move |channel| {
+            COMMANDS.into_iter().flat_map(move |command| {
+                STRENGTHS.into_iter().map(move |strength| CommandPacket {
+                    id,
+                    channel,
+                    command,
+                    strength,
+                })
+            })
+        })
+    })
+}
+
+#[test]
+fn encoded_packets_decode() {
+    for packet in packets() {
+        let source = RefCell::new(MockTimingSource::default());
+        let transmitter = timing::transmitter(NoPin, &source, packet.id);
+        let timings = transmitter.encode_packet(
+            packet.channel,
+            packet.command,
+            packet.strength,
+        );
+        let timings = timings.as_slice();
+        let per_us = u64::from(timings[0]) / PREAMBLE_LOW_US;
+
+        // The timings alternate between low and high, starting low
+        let mut decoder = PacketDecoder::new();
+        let decoded = timings
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .find_map(|&high| decoder.high_pulse(u64::from(high) / per_us));
+        assert_eq!(decoded, Some(packet));
+    }
+}
+
+#[cfg(feature = "simulator")]
+#[test]
+fn simulated_collar_receives_packets() {
+    use zap_me::ch8803::Transmitter;
+    use zap_me::simulator::SimulatedCollar;
+
+    for packet in packets() {
+        let collar = SimulatedCollar::new();
+        let delay = RefCell::new(collar.delay());
+        let mut transmitter = Transmitter::builder()
+            .pin(collar.pin())
+            .delay(&delay)
+            .now_fn(collar.now_fn())
+            .id(packet.id)
+            .build();
+        let Ok(_) = transmitter.send_command_n(
+            packet.channel,
+            packet.command,
+            packet.strength,
+            1,
+        );
+        collar.assert_received(packet);
+    }
+}
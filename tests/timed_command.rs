@@ -0,0 +1,34 @@
+//! Decodes a [TimedCommand] from JSON, as sent by a PC over a serial port,
+//! and sends it.
+
+#![cfg(feature = "serde")]
+
+use core::cell::RefCell;
+use zap_me::ch8803::{Channel, Command, TimedCommand};
+use zap_me::indicator::NoPin;
+use zap_me::timing::{self, MockTimingSource};
+
+const JSON: &str = r#"{"channel":"channel2","command":"vibrate","strength":40,"duration_ms":250}"#;
+
+#[test]
+fn json_round_trip() {
+    let command: TimedCommand = serde_json::from_str(JSON).unwrap();
+    assert_eq!(
+        command,
+        TimedCommand {
+            channel: Channel::Channel2,
+            command: Command::Vibrate,
+            strength: 40,
+            duration_ms: 250,
+        }
+    );
+    assert_eq!(serde_json::to_string(&command).unwrap(), JSON);
+}
+
+#[test]
+fn execute_decoded_command() {
+    let command: TimedCommand = serde_json::from_str(JSON).unwrap();
+    let source = RefCell::new(MockTimingSource::default());
+    let mut transmitter = timing::transmitter(NoPin, &source, 0x0d25);
+    assert!(transmitter.execute(&command) > 0);
+}
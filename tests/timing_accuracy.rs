@@ -0,0 +1,30 @@
+//! Checks that a command takes as long as its packets should, so that
+//! changes to the packet structure that lengthen or shorten it are noticed.
+
+use core::cell::RefCell;
+use zap_me::indicator::NoPin;
+use zap_me::timing::{self, MockTimingSource, TimingSource};
+
+/// One repetition as sent by the original remote in microseconds: the
+/// preamble, 42 bits of 1016 µs, the footer and the inter-packet gap.
+const PACKET_PERIOD_US: u64 =
+    (840 + 1440 + 724) + 42 * 1016 + (292 + 1476) + 10_000;
+
+#[test]
+fn vibrate_matches_theoretical_duration() {
+    let source = RefCell::new(MockTimingSource::default());
+    let mut transmitter = timing::transmitter(NoPin, &source, 0x0d25);
+
+    let start = source.borrow().now();
+    transmitter.channel(0).unwrap().vibrate_ms(50, 1000);
+    let elapsed_us = (source.borrow().now() - start).to_micros();
+
+    // The last repetition started before the end is always completed
+    let repetitions = 1_000_000u64.div_ceil(PACKET_PERIOD_US);
+    let expected_us = repetitions * PACKET_PERIOD_US;
+    let deviation = elapsed_us.abs_diff(expected_us);
+    assert!(
+        deviation * 100 < expected_us * 5,
+        "took {elapsed_us} µs, expected {expected_us} µs"
+    );
+}